@@ -0,0 +1,310 @@
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map};
+use twilight_model::{
+    application::interaction::Interaction,
+    channel::message::{
+        component::{ActionRow, ButtonStyle},
+        Component,
+    },
+    http::interaction::InteractionResponseData,
+    id::Id,
+};
+
+use crate::{
+    constants,
+    context::Context,
+    error::{Error, Result},
+    interactions::{self, respond_ephemeral, update_message},
+    metrics::{self, DISCORD_REQUEST_DURATION},
+    models::JiraNamedField,
+    utils,
+};
+
+/// prefix of the "Me too" button's custom id, followed by the thread id
+pub const AFFECTED_BUTTON_ID: &str = "affected";
+
+/// A Discord user who said they're affected by the bug too.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct AffectedUser {
+    pub id: u64,
+    pub name: String,
+}
+
+/// The "Me too" button, labelled with how many users are affected so far.
+pub fn me_too_buttons(thread_id: u64, count: usize) -> Vec<Component> {
+    let label = if count == 0 {
+        "Me too".to_owned()
+    } else {
+        format!("Me too ({})", count)
+    };
+
+    vec![Component::ActionRow(ActionRow {
+        components: vec![interactions::button(
+            format!("{}:{}", AFFECTED_BUTTON_ID, thread_id),
+            &label,
+            ButtonStyle::Secondary,
+        )],
+    })]
+}
+
+/// Posts the bot's sync reply in the thread, with the "Me too" button under it.
+pub async fn send_sync_reply(ctx: &Context, thread_id: u64, content: &str) -> Result<()> {
+    let count = ctx
+        .links
+        .get(thread_id)
+        .map_or(0, |link| link.affected_users.len());
+
+    let components = me_too_buttons(thread_id, count);
+    let request = ctx
+        .discord
+        .create_message(Id::new(thread_id))
+        .content(content)?
+        .components(&components)?;
+    metrics::observe(&DISCORD_REQUEST_DURATION, "create_message", request).await?;
+
+    Ok(())
+}
+
+/// Toggles whether the user is affected by the thread's bug and pushes the new list to Jira.
+pub async fn handle_button(ctx: &Context, interaction: &Interaction, target: &str) -> Result<()> {
+    let thread_id = target
+        .parse::<u64>()
+        .map_err(|_| Error::Parse(format!("invalid affected target: {}", target)))?;
+
+    let user = interaction
+        .author()
+        .ok_or_else(|| Error::Parse("me too clicked without a user".to_owned()))?;
+
+    if ctx.links.get(thread_id).is_none() {
+        return respond_ephemeral(
+            ctx,
+            interaction,
+            "This thread isn't linked to a Jira issue anymore.",
+        )
+        .await;
+    }
+
+    ctx.links.update(thread_id, |link| {
+        let user_id = user.id.get();
+
+        if link
+            .affected_users
+            .iter()
+            .any(|affected| affected.id == user_id)
+        {
+            link.affected_users
+                .retain(|affected| affected.id != user_id);
+        } else {
+            link.affected_users.push(AffectedUser {
+                id: user_id,
+                name: user.name.clone(),
+            });
+        }
+    })?;
+
+    let link = match ctx.links.get(thread_id) {
+        Some(link) => link,
+        None => return Ok(()),
+    };
+
+    tracing::info!(
+        thread_id,
+        count = link.affected_users.len(),
+        "affected users changed"
+    );
+
+    // show the new count right away, jira can catch up after
+    update_message(
+        ctx,
+        interaction,
+        InteractionResponseData {
+            components: Some(me_too_buttons(thread_id, link.affected_users.len())),
+            ..Default::default()
+        },
+    )
+    .await?;
+
+    sync_to_jira(ctx, &link.issue_key).await?;
+    raise_priority(ctx, &link.issue_key).await
+}
+
+/// Users affected by the issue across every thread it's linked to.
+fn affected_users(ctx: &Context, issue_key: &str) -> Vec<AffectedUser> {
+    let mut users = Vec::<AffectedUser>::new();
+
+    for link in ctx.links.find_all_by_issue(issue_key) {
+        for user in link.affected_users {
+            if !users.iter().any(|affected| affected.id == user.id) {
+                users.push(user);
+            }
+        }
+    }
+
+    users
+}
+
+/// Pushes the affected count and users to the configured Jira fields and label.
+async fn sync_to_jira(ctx: &Context, issue_key: &str) -> Result<()> {
+    let affected_users = affected_users(ctx, issue_key);
+    let mut fields = Map::new();
+
+    if let Some(field) = constants::AFFECTED_COUNT_FIELD.as_ref() {
+        fields.insert(field.clone(), json!(affected_users.len()));
+    }
+
+    if let Some(field) = constants::AFFECTED_USERS_FIELD.as_ref() {
+        let users = affected_users
+            .iter()
+            .map(|user| format!("{} ({})", user.name, user.id))
+            .collect::<Vec<_>>()
+            .join(", ");
+        fields.insert(field.clone(), json!(users));
+    }
+
+    let mut body = json!({ "fields": fields });
+
+    if let Some(label) = constants::AFFECTED_LABEL.as_ref() {
+        let operation = if affected_users.is_empty() {
+            "remove"
+        } else {
+            "add"
+        };
+        body["update"] = json!({ "labels": [{ operation: label }] });
+    } else if fields.is_empty() {
+        return Ok(());
+    }
+
+    utils::update_jira_issue(ctx, issue_key, &body).await
+}
+
+/// Priority names in Jira's order, most urgent first.
+async fn priority_order(ctx: &Context) -> Result<Vec<String>> {
+    let priorities = ctx
+        .jira
+        .send("get_priorities", ctx.jira.request(Method::GET, "priority"))
+        .await?
+        .json::<Vec<JiraNamedField>>()
+        .await
+        .map_err(|error| Error::Parse(error.to_string()))?;
+
+    Ok(priorities
+        .into_iter()
+        .map(|priority| priority.name)
+        .collect())
+}
+
+/// Whether going from the current priority to the target one is a raise. Priorities that
+/// aren't in Jira's order can't be compared, so they're left alone.
+fn is_raise(order: &[String], current: Option<&str>, target: &str) -> bool {
+    let position = |priority: &str| {
+        order
+            .iter()
+            .position(|name| name.eq_ignore_ascii_case(priority))
+    };
+
+    match (current.map(position), position(target)) {
+        // issues without a priority can always get one
+        (None, Some(_)) => true,
+        (Some(Some(current)), Some(target)) => target < current,
+        _ => false,
+    }
+}
+
+/// Raises the issue's priority once the affected count reaches a configured threshold.
+///
+/// Priorities are only ever raised, and each threshold is only applied once, so staff can
+/// still lower the priority by hand afterwards.
+async fn raise_priority(ctx: &Context, issue_key: &str) -> Result<()> {
+    let count = affected_users(ctx, issue_key).len();
+    let links = ctx.links.find_all_by_issue(issue_key);
+    let applied = links
+        .iter()
+        .map(|link| link.priority_threshold)
+        .max()
+        .unwrap_or_default();
+
+    let (threshold, priority) = match constants::PRIORITY_THRESHOLDS
+        .iter()
+        .filter(|(threshold, _)| count >= *threshold && *threshold > applied)
+        .max_by_key(|(threshold, _)| *threshold)
+    {
+        Some((threshold, priority)) => (*threshold, priority),
+        None => return Ok(()),
+    };
+
+    // staff may have set a higher priority already
+    let issue = utils::get_jira_issue(ctx, issue_key).await?;
+    let current = issue
+        .fields
+        .priority
+        .as_ref()
+        .map(|current| current.name.as_str());
+    let raise = is_raise(&priority_order(ctx).await?, current, priority);
+
+    if raise {
+        utils::update_jira_issue(
+            ctx,
+            issue_key,
+            &json!({ "fields": { "priority": { "name": priority } } }),
+        )
+        .await?;
+    }
+
+    for link in &links {
+        ctx.links
+            .update(link.thread_id, |link| link.priority_threshold = threshold)?;
+    }
+
+    if !raise {
+        return Ok(());
+    }
+
+    tracing::info!(
+        jira_key = issue_key,
+        count,
+        %priority,
+        "raised priority for affected users"
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn order() -> Vec<String> {
+        ["Blocker", "Highest", "High", "Medium", "Low"]
+            .into_iter()
+            .map(str::to_owned)
+            .collect()
+    }
+
+    #[test]
+    fn raises_lower_priorities() {
+        assert!(is_raise(&order(), Some("Medium"), "High"));
+        assert!(is_raise(&order(), Some("low"), "Highest"));
+    }
+
+    #[test]
+    fn never_lowers_or_repeats_a_priority() {
+        assert!(!is_raise(&order(), Some("Highest"), "High"));
+        assert!(!is_raise(&order(), Some("Blocker"), "High"));
+        assert!(!is_raise(&order(), Some("High"), "High"));
+    }
+
+    #[test]
+    fn issues_without_a_priority_get_one() {
+        assert!(is_raise(&order(), None, "High"));
+    }
+
+    #[test]
+    fn unranked_priorities_are_left_alone() {
+        // e.g. a priority scheme that was changed after the thresholds were configured
+        assert!(!is_raise(&order(), Some("Critical"), "High"));
+        assert!(!is_raise(&order(), Some("Medium"), "Urgent"));
+        assert!(!is_raise(&[], Some("Medium"), "High"));
+    }
+}
@@ -0,0 +1,225 @@
+use std::time::Duration;
+
+use serde_json::Value;
+use twilight_model::{
+    channel::Channel,
+    guild::{audit_log::AuditLogEventType, Permissions},
+    id::{
+        marker::{GuildMarker, UserMarker},
+        Id,
+    },
+};
+
+use crate::{
+    constants,
+    context::Context,
+    error::Result,
+    metrics::{self, DISCORD_REQUEST_DURATION},
+    models::{AuditLog, AuditLogChange},
+    utils,
+};
+
+/// how many times the audit log is checked for a thread update, it can lag behind the gateway event
+const AUDIT_LOG_ATTEMPTS: u32 = 3;
+const AUDIT_LOG_DELAY: Duration = Duration::from_secs(1);
+/// how long before the gateway event its audit log entry can have been written
+const MAX_EVENT_LAG_MS: u64 = 10_000;
+/// allowance for our clock running behind discord's
+const MAX_CLOCK_SKEW_MS: u64 = 2_000;
+/// milliseconds between the unix epoch and the discord epoch, snowflakes count from the latter
+const DISCORD_EPOCH_MS: u64 = 1_420_070_400_000;
+
+/// Whether the audit log change applied the tag, i.e. it's in the new tags but not the old ones.
+fn adds_tag(change: &AuditLogChange, tag_id: u64) -> bool {
+    // tag ids are snowflake strings
+    let tag_id = tag_id.to_string();
+    let has_tag = |tags: &Option<Value>| {
+        tags.as_ref()
+            .and_then(Value::as_array)
+            .is_some_and(|tags| tags.iter().any(|tag| tag.as_str() == Some(&tag_id)))
+    };
+
+    change.key == "applied_tags" && has_tag(&change.new_value) && !has_tag(&change.old_value)
+}
+
+/// Who applied the tag to the thread according to the guild audit log.
+///
+/// Only entries written around `received_at`, the unix time in milliseconds the gateway
+/// event arrived, are considered, and the bot's own tag changes are ignored.
+pub async fn tag_applied_by(
+    ctx: &Context,
+    thread_id: u64,
+    tag_id: u64,
+    received_at: u64,
+) -> Result<Option<Id<UserMarker>>> {
+    let bot_user_id = ctx.bot_user_id.get().copied();
+
+    for attempt in 1..=AUDIT_LOG_ATTEMPTS {
+        let request = ctx
+            .discord
+            .audit_log(Id::<GuildMarker>::new(*constants::GUILD_ID))
+            .action_type(AuditLogEventType::ThreadUpdate)
+            .limit(10)
+            .expect("10 is a valid audit log limit");
+        let bytes = metrics::observe(&DISCORD_REQUEST_DURATION, "get_audit_log", request)
+            .await?
+            .bytes()
+            .await?;
+        let audit_log = serde_json::from_slice::<AuditLog>(&bytes)?;
+
+        // entries are newest first
+        let entry = audit_log.audit_log_entries.into_iter().find(|entry| {
+            let created_at = (entry.id.get() >> 22) + DISCORD_EPOCH_MS;
+
+            entry.target_id.map(|target_id| target_id.get()) == Some(thread_id)
+                && entry.user_id.map(|user_id| user_id.get()) != bot_user_id
+                && created_at + MAX_EVENT_LAG_MS >= received_at
+                && created_at <= received_at + MAX_CLOCK_SKEW_MS
+                && entry.changes.iter().any(|change| adds_tag(change, tag_id))
+        });
+
+        if let Some(entry) = entry {
+            return Ok(entry.user_id);
+        }
+
+        if attempt < AUDIT_LOG_ATTEMPTS {
+            tokio::time::sleep(AUDIT_LOG_DELAY).await;
+        }
+    }
+
+    Ok(None)
+}
+
+/// Whether the member has one of the staff roles or the permissions needed to trigger a sync.
+pub async fn can_trigger_sync(ctx: &Context, user_id: Id<UserMarker>) -> Result<bool> {
+    let guild_id = Id::<GuildMarker>::new(*constants::GUILD_ID);
+
+    let member = ctx.discord.guild_member(guild_id, user_id);
+    let member = metrics::observe(&DISCORD_REQUEST_DURATION, "get_guild_member", member)
+        .await?
+        .model()
+        .await?;
+
+    if member
+        .roles
+        .iter()
+        .any(|role| constants::STAFF_ROLE_IDS.contains(&role.get()))
+    {
+        return Ok(true);
+    }
+
+    let roles = ctx.discord.roles(guild_id);
+    let roles = metrics::observe(&DISCORD_REQUEST_DURATION, "get_roles", roles)
+        .await?
+        .models()
+        .await?;
+
+    // the @everyone role shares its id with the guild
+    let permissions = roles
+        .iter()
+        .filter(|role| role.id.cast() == guild_id || member.roles.contains(&role.id))
+        .fold(Permissions::empty(), |permissions, role| {
+            permissions | role.permissions
+        });
+
+    Ok(permissions.contains(Permissions::ADMINISTRATOR)
+        || permissions.contains(*constants::SYNC_TAG_PERMISSIONS))
+}
+
+/// Whether the sync tag that was just applied to the thread was applied by someone allowed to.
+/// `received_at` is the unix time in milliseconds the thread update arrived.
+///
+/// Unauthorized tag changes are reverted and the thread gets a reply explaining why.
+pub async fn check_sync_tag_authorized(
+    ctx: &Context,
+    thread: &Channel,
+    sync_tag_id: u64,
+    received_at: u64,
+) -> Result<bool> {
+    let thread_id = thread.id.get();
+
+    let authorized = match tag_applied_by(ctx, thread_id, sync_tag_id, received_at).await? {
+        Some(user_id) => can_trigger_sync(ctx, user_id).await?,
+        None => {
+            tracing::warn!(thread_id, "couldn't find who applied the sync tag");
+            false
+        }
+    };
+
+    if authorized {
+        return Ok(true);
+    }
+
+    tracing::info!(thread_id, "reverting unauthorized sync tag");
+
+    let tags = thread.applied_tags.as_deref().unwrap_or_default();
+    utils::remove_sync_tag(ctx, thread_id, tags).await?;
+
+    utils::send_update_to_user_report(
+        ctx,
+        thread_id,
+        "Only staff can send reports to our bug tracking system, so the sync tag was removed. A moderator will add it once the report has been triaged.",
+    )
+    .await?;
+
+    Ok(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn change(key: &str, old: Value, new: Value) -> AuditLogChange {
+        AuditLogChange {
+            key: key.to_owned(),
+            old_value: Some(old),
+            new_value: Some(new),
+        }
+    }
+
+    #[test]
+    fn tag_added() {
+        let change = change("applied_tags", json!(["1"]), json!(["1", "42"]));
+
+        assert!(adds_tag(&change, 42));
+    }
+
+    #[test]
+    fn tag_already_present() {
+        let change = change("applied_tags", json!(["42"]), json!(["42", "7"]));
+
+        assert!(!adds_tag(&change, 42));
+    }
+
+    #[test]
+    fn tag_removed_or_other_tag_added() {
+        assert!(!adds_tag(
+            &change("applied_tags", json!(["42"]), json!([])),
+            42
+        ));
+        assert!(!adds_tag(
+            &change("applied_tags", json!([]), json!(["7"])),
+            42
+        ));
+    }
+
+    #[test]
+    fn first_tags_have_no_old_value() {
+        let change = AuditLogChange {
+            key: "applied_tags".to_owned(),
+            old_value: None,
+            new_value: Some(json!(["42"])),
+        };
+
+        assert!(adds_tag(&change, 42));
+    }
+
+    #[test]
+    fn other_changes_dont_count() {
+        let change = change("name", json!("42"), json!(["42"]));
+
+        assert!(!adds_tag(&change, 42));
+    }
+}
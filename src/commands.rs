@@ -0,0 +1,852 @@
+use std::sync::Arc;
+
+use twilight_model::{
+    application::{
+        command::{Command, CommandOption, CommandOptionType, CommandType},
+        interaction::{
+            application_command::{CommandData, CommandDataOption, CommandOptionValue},
+            modal::ModalInteractionData,
+            Interaction,
+        },
+    },
+    channel::message::component::TextInputStyle,
+    http::attachment::Attachment,
+    id::{
+        marker::{ApplicationMarker, ChannelMarker, GuildMarker, MessageMarker},
+        Id,
+    },
+};
+
+use crate::{
+    affected, constants,
+    context::Context,
+    embeds,
+    error::{Error, Result},
+    interactions::{
+        self, bug_report_thread_id, defer_ephemeral, modal_value, respond_ephemeral, respond_modal,
+        text_input, update_deferred,
+    },
+    jobs::SyncJob,
+    metrics::{self, DISCORD_REQUEST_DURATION},
+    report::{self, BugReport},
+    review, status,
+    storage::IssueLink,
+    subscriptions, utils,
+};
+
+pub const JIRA_COMMAND_NAME: &str = "jira";
+pub const SEND_TO_JIRA_COMMAND_NAME: &str = "Send to Jira";
+/// prefix of the send to Jira modal's custom id, followed by the channel and message ids
+pub const SEND_TO_JIRA_MODAL_ID: &str = "send_to_jira";
+pub const REPORT_BUG_COMMAND_NAME: &str = "report-bug";
+pub const REPORT_BUG_MODAL_ID: &str = "report_bug";
+
+/// Registers the application commands for the guild, replacing whatever was registered before.
+pub async fn register_commands(ctx: &Context, application_id: Id<ApplicationMarker>) -> Result<()> {
+    let commands = [jira_command(), send_to_jira_command(), report_bug_command()];
+
+    let interaction_client = ctx.discord.interaction(application_id);
+    let request = interaction_client
+        .set_guild_commands(Id::<GuildMarker>::new(*constants::GUILD_ID), &commands);
+    metrics::observe(&DISCORD_REQUEST_DURATION, "set_guild_commands", request).await?;
+
+    tracing::info!(count = commands.len(), "registered guild commands");
+
+    Ok(())
+}
+
+fn jira_command() -> Command {
+    Command {
+        application_id: None,
+        default_member_permissions: None,
+        dm_permission: Some(false),
+        description: "Manage Jira issues linked to bug reports and your subscriptions to them"
+            .to_owned(),
+        description_localizations: None,
+        guild_id: None,
+        id: None,
+        kind: CommandType::ChatInput,
+        name: JIRA_COMMAND_NAME.to_owned(),
+        name_localizations: None,
+        nsfw: None,
+        options: vec![
+            subcommand("sync", "Sync this thread to Jira now", Vec::new()),
+            subcommand(
+                "link",
+                "Link this thread to an existing Jira issue",
+                vec![string_option(
+                    "key",
+                    "Key of the Jira issue, e.g. CELLS-123",
+                    true,
+                )],
+            ),
+            subcommand(
+                "unlink",
+                "Remove the link between this thread and its Jira issue",
+                Vec::new(),
+            ),
+            subcommand(
+                "status",
+                "Show the live status of the linked Jira issue",
+                Vec::new(),
+            ),
+            subcommand("open", "Get the Jira URL of the linked issue", Vec::new()),
+            subcommand(
+                "override",
+                "Let this thread sync even though it doesn't match the bug report template",
+                Vec::new(),
+            ),
+            subcommand(
+                "subscribe",
+                "Get a DM whenever a Jira issue changes",
+                vec![string_option(
+                    "key",
+                    "Key of the Jira issue, defaults to the one linked to this thread",
+                    false,
+                )],
+            ),
+            subcommand(
+                "unsubscribe",
+                "Stop getting DMs about a Jira issue",
+                vec![string_option(
+                    "key",
+                    "Key of the Jira issue, defaults to the one linked to this thread",
+                    false,
+                )],
+            ),
+            subcommand(
+                "notifications",
+                "Choose which issue updates you get DMs about",
+                vec![
+                    boolean_option("dms", "Get subscription DMs at all", false),
+                    boolean_option("status", "DMs about status changes", false),
+                    boolean_option(
+                        "resolution",
+                        "DMs about issues being resolved or reopened",
+                        false,
+                    ),
+                    boolean_option("comments", "DMs about new comments", false),
+                ],
+            ),
+            subcommand(
+                "unfurl",
+                "Turn replying with a summary of mentioned issues on or off in this channel",
+                vec![boolean_option(
+                    "enabled",
+                    "Whether issue keys are unfurled here",
+                    true,
+                )],
+            ),
+        ],
+        version: Id::new(1),
+    }
+}
+
+/// Message context menu command for reporting a message posted outside the bug report forum.
+fn send_to_jira_command() -> Command {
+    Command {
+        application_id: None,
+        default_member_permissions: None,
+        dm_permission: Some(false),
+        // message commands can't have a description
+        description: String::new(),
+        description_localizations: None,
+        guild_id: None,
+        id: None,
+        kind: CommandType::Message,
+        name: SEND_TO_JIRA_COMMAND_NAME.to_owned(),
+        name_localizations: None,
+        nsfw: None,
+        options: Vec::new(),
+        version: Id::new(1),
+    }
+}
+
+fn report_bug_command() -> Command {
+    Command {
+        application_id: None,
+        default_member_permissions: None,
+        dm_permission: Some(false),
+        description: "Report a bug using a form".to_owned(),
+        description_localizations: None,
+        guild_id: None,
+        id: None,
+        kind: CommandType::ChatInput,
+        name: REPORT_BUG_COMMAND_NAME.to_owned(),
+        name_localizations: None,
+        nsfw: None,
+        options: Vec::new(),
+        version: Id::new(1),
+    }
+}
+
+pub fn subcommand(name: &str, description: &str, options: Vec<CommandOption>) -> CommandOption {
+    CommandOption {
+        kind: CommandOptionType::SubCommand,
+        options: Some(options),
+        ..option(name, description)
+    }
+}
+
+pub fn string_option(name: &str, description: &str, required: bool) -> CommandOption {
+    CommandOption {
+        kind: CommandOptionType::String,
+        required: Some(required),
+        ..option(name, description)
+    }
+}
+
+pub fn boolean_option(name: &str, description: &str, required: bool) -> CommandOption {
+    CommandOption {
+        kind: CommandOptionType::Boolean,
+        required: Some(required),
+        ..option(name, description)
+    }
+}
+
+fn option(name: &str, description: &str) -> CommandOption {
+    CommandOption {
+        autocomplete: None,
+        channel_types: None,
+        choices: None,
+        description: description.to_owned(),
+        description_localizations: None,
+        kind: CommandOptionType::String,
+        max_length: None,
+        max_value: None,
+        min_length: None,
+        min_value: None,
+        name: name.to_owned(),
+        name_localizations: None,
+        options: None,
+        required: None,
+    }
+}
+
+/// Value of a string option passed to a subcommand.
+pub fn string_value<'a>(options: &'a [CommandDataOption], name: &str) -> Option<&'a str> {
+    options.iter().find_map(|option| match &option.value {
+        CommandOptionValue::String(value) if option.name == name => Some(value.as_str()),
+        _ => None,
+    })
+}
+
+/// Value of a boolean option passed to a subcommand.
+pub fn boolean_value(options: &[CommandDataOption], name: &str) -> Option<bool> {
+    options.iter().find_map(|option| match option.value {
+        CommandOptionValue::Boolean(value) if option.name == name => Some(value),
+        _ => None,
+    })
+}
+
+pub async fn handle_jira_command(
+    ctx: &Arc<Context>,
+    interaction: &Interaction,
+    data: &CommandData,
+) -> Result<()> {
+    let (subcommand, options) = match data.options.first() {
+        Some(CommandDataOption {
+            name,
+            value: CommandOptionValue::SubCommand(options),
+        }) => (name.as_str(), options.as_slice()),
+        _ => return Ok(()),
+    };
+
+    // subscriptions are personal, anyone can manage theirs from anywhere
+    match subcommand {
+        "subscribe" => return subscribe(ctx, interaction, options).await,
+        "unsubscribe" => return unsubscribe(ctx, interaction, options).await,
+        "notifications" => return notifications(ctx, interaction, options).await,
+        _ => {}
+    }
+
+    if !interactions::is_staff(interaction) {
+        return respond_ephemeral(ctx, interaction, "Only staff can manage Jira links.").await;
+    }
+
+    // unfurling is set per channel, not just in bug report threads
+    if subcommand == "unfurl" {
+        return unfurl(ctx, interaction, options).await;
+    }
+
+    let thread_id = match bug_report_thread_id(interaction) {
+        Some(thread_id) => thread_id,
+        None => {
+            return respond_ephemeral(
+                ctx,
+                interaction,
+                "This command only works in a bug report thread.",
+            )
+            .await
+        }
+    };
+
+    match subcommand {
+        "sync" => sync(ctx, interaction, thread_id).await,
+        "link" => {
+            let key = string_value(options, "key")
+                .unwrap_or_default()
+                .to_uppercase();
+            link(ctx, interaction, thread_id, &key).await
+        }
+        "unlink" => unlink(ctx, interaction, thread_id).await,
+        "status" => status(ctx, interaction, thread_id).await,
+        "open" => open(ctx, interaction, thread_id).await,
+        "override" => override_template(ctx, interaction, thread_id).await,
+        _ => Ok(()),
+    }
+}
+
+async fn sync(ctx: &Arc<Context>, interaction: &Interaction, thread_id: u64) -> Result<()> {
+    if let Some(link) = ctx.links.get(thread_id) {
+        let content = format!("This thread is already synced as {}.", link.issue_key);
+        return respond_ephemeral(ctx, interaction, &content).await;
+    }
+
+    ctx.jobs.spawn(ctx, SyncJob::new(thread_id));
+
+    respond_ephemeral(
+        ctx,
+        interaction,
+        "Syncing this thread to Jira, the issue key will be posted here once it's created.",
+    )
+    .await
+}
+
+async fn link(ctx: &Context, interaction: &Interaction, thread_id: u64, key: &str) -> Result<()> {
+    if let Some(link) = ctx.links.get(thread_id) {
+        let content = format!(
+            "This thread is already linked to {}, use `/jira unlink` first.",
+            link.issue_key
+        );
+        return respond_ephemeral(ctx, interaction, &content).await;
+    }
+
+    if !utils::is_issue_key(key) {
+        let content = format!(
+            "`{}` isn't a valid issue key, it should look like CELLS-123.",
+            key
+        );
+        return respond_ephemeral(ctx, interaction, &content).await;
+    }
+
+    defer_ephemeral(ctx, interaction).await?;
+
+    let issue = match utils::get_jira_issue(ctx, key).await {
+        Ok(issue) => issue,
+        Err(error) if error.is_not_found() => {
+            let content = format!("Couldn't find {} in Jira.", key);
+            return update_deferred(ctx, interaction, &content).await;
+        }
+        Err(error) => return Err(error),
+    };
+
+    let title = interaction
+        .channel
+        .as_ref()
+        .and_then(|channel| channel.name.clone());
+    link_thread(ctx, thread_id, &issue.key, title).await?;
+
+    let content = format!(
+        "Linked this thread to {}: {}",
+        issue.key, issue.fields.summary
+    );
+    update_deferred(ctx, interaction, &content).await
+}
+
+/// Links the thread to an existing issue, telling both sides about it.
+pub async fn link_thread(
+    ctx: &Context,
+    thread_id: u64,
+    issue_key: &str,
+    title: Option<String>,
+) -> Result<()> {
+    let message = utils::get_starter_message(ctx, thread_id).await?;
+    ctx.links.insert(IssueLink {
+        thread_id,
+        issue_key: issue_key.to_owned(),
+        reporter_id: status::reporter_id(ctx, &message),
+        title,
+        description: Some(utils::report_description(&message.content)),
+        ..Default::default()
+    })?;
+
+    let comment = format!("Linked to Discord thread: {}", utils::thread_url(thread_id));
+    utils::add_jira_comment(ctx, issue_key, &comment).await?;
+
+    let mut automated_reply = format!("This report is now tracked as the issue {}.  As we resolve that issue, updates will be posted back here.", issue_key);
+    if let Some(primary_thread_id) = ctx.links.get(thread_id).and_then(|link| link.follows) {
+        automated_reply.push_str(&format!(
+            " It was first reported in <#{}>.",
+            primary_thread_id
+        ));
+    }
+    affected::send_sync_reply(ctx, thread_id, &automated_reply).await?;
+
+    status::post_status_card(ctx, thread_id).await
+}
+
+async fn unlink(ctx: &Context, interaction: &Interaction, thread_id: u64) -> Result<()> {
+    let link = match ctx.links.remove(thread_id)? {
+        Some(link) => link,
+        None => {
+            return respond_ephemeral(
+                ctx,
+                interaction,
+                "This thread isn't linked to a Jira issue.",
+            )
+            .await
+        }
+    };
+
+    defer_ephemeral(ctx, interaction).await?;
+
+    let comment = format!(
+        "Unlinked from Discord thread: {}",
+        utils::thread_url(thread_id)
+    );
+    utils::add_jira_comment(ctx, &link.issue_key, &comment).await?;
+
+    let content = format!("Unlinked this thread from {}.", link.issue_key);
+    update_deferred(ctx, interaction, &content).await
+}
+
+async fn status(ctx: &Context, interaction: &Interaction, thread_id: u64) -> Result<()> {
+    let link = match ctx.links.get(thread_id) {
+        Some(link) => link,
+        None => {
+            return respond_ephemeral(
+                ctx,
+                interaction,
+                "This thread isn't linked to a Jira issue.",
+            )
+            .await
+        }
+    };
+
+    defer_ephemeral(ctx, interaction).await?;
+
+    let issue = utils::get_jira_issue(ctx, &link.issue_key).await?;
+    let content = embeds::issue_text(&issue, None);
+
+    update_deferred(ctx, interaction, &content).await
+}
+
+async fn open(ctx: &Context, interaction: &Interaction, thread_id: u64) -> Result<()> {
+    let content = match ctx.links.get(thread_id) {
+        Some(link) => utils::issue_url(&link.issue_key),
+        None => "This thread isn't linked to a Jira issue.".to_owned(),
+    };
+
+    respond_ephemeral(ctx, interaction, &content).await
+}
+
+async fn override_template(ctx: &Context, interaction: &Interaction, thread_id: u64) -> Result<()> {
+    let moderator_id = interaction
+        .author_id()
+        .map_or(0, |moderator_id| moderator_id.get());
+    ctx.template_overrides.insert(thread_id, moderator_id)?;
+
+    tracing::info!(thread_id, moderator_id, "bug report template overridden");
+
+    respond_ephemeral(
+        ctx,
+        interaction,
+        "This thread can now be synced without matching the template, apply the sync tag or use `/jira sync`.",
+    )
+    .await
+}
+
+async fn unfurl(
+    ctx: &Context,
+    interaction: &Interaction,
+    options: &[CommandDataOption],
+) -> Result<()> {
+    let channel_id = match interaction.channel.as_ref() {
+        Some(channel) => channel.id.get(),
+        None => return Ok(()),
+    };
+    let enabled = boolean_value(options, "enabled").unwrap_or(true);
+    ctx.unfurl_channels.insert(channel_id, enabled)?;
+
+    tracing::info!(channel_id, enabled, "issue unfurling changed");
+
+    let content = if enabled {
+        "Issue keys mentioned in this channel will be unfurled."
+    } else {
+        "Issue keys mentioned in this channel won't be unfurled anymore."
+    };
+    respond_ephemeral(ctx, interaction, content).await
+}
+
+/// Issue key passed to a subscription subcommand, or else the one linked to the current thread.
+fn subscription_key(
+    ctx: &Context,
+    interaction: &Interaction,
+    options: &[CommandDataOption],
+) -> Option<String> {
+    match string_value(options, "key") {
+        Some(key) => Some(key.trim().to_uppercase()),
+        None => bug_report_thread_id(interaction)
+            .and_then(|thread_id| ctx.links.get(thread_id))
+            .map(|link| link.issue_key),
+    }
+}
+
+async fn subscribe(
+    ctx: &Context,
+    interaction: &Interaction,
+    options: &[CommandDataOption],
+) -> Result<()> {
+    let (key, user_id) =
+        match subscription_key(ctx, interaction, options).zip(interaction.author_id()) {
+            Some(found) => found,
+            None => {
+                return respond_ephemeral(
+                    ctx,
+                    interaction,
+                    "Pass the key of the issue to subscribe to, e.g. `/jira subscribe CELLS-123`.",
+                )
+                .await
+            }
+        };
+
+    if !utils::is_issue_key(&key) {
+        let content = format!(
+            "`{}` isn't a valid issue key, it should look like CELLS-123.",
+            key
+        );
+        return respond_ephemeral(ctx, interaction, &content).await;
+    }
+
+    if !subscriptions::is_public(ctx, &key) {
+        let content = format!(
+            "{} isn't tracked in a bug report thread, so you can't subscribe to it.",
+            key
+        );
+        return respond_ephemeral(ctx, interaction, &content).await;
+    }
+
+    defer_ephemeral(ctx, interaction).await?;
+
+    // make sure the issue exists, there'd never be any updates otherwise
+    match utils::get_jira_issue(ctx, &key).await {
+        Ok(_) => {}
+        Err(error) if error.is_not_found() => {
+            let content = format!("Couldn't find {} in Jira.", key);
+            return update_deferred(ctx, interaction, &content).await;
+        }
+        Err(error) => return Err(error),
+    }
+
+    let content = if subscriptions::subscribe(ctx, user_id.get(), &key)? {
+        format!("You'll get a DM whenever {} changes.", key)
+    } else {
+        format!("You're already subscribed to {}.", key)
+    };
+
+    update_deferred(ctx, interaction, &content).await
+}
+
+async fn unsubscribe(
+    ctx: &Context,
+    interaction: &Interaction,
+    options: &[CommandDataOption],
+) -> Result<()> {
+    let (key, user_id) = match subscription_key(ctx, interaction, options)
+        .zip(interaction.author_id())
+    {
+        Some(found) => found,
+        None => return respond_ephemeral(
+            ctx,
+            interaction,
+            "Pass the key of the issue to unsubscribe from, e.g. `/jira unsubscribe CELLS-123`.",
+        )
+        .await,
+    };
+
+    let content = if subscriptions::unsubscribe(ctx, user_id.get(), &key)? {
+        format!("You won't get DMs about {} anymore.", key)
+    } else {
+        format!("You weren't subscribed to {}.", key)
+    };
+
+    respond_ephemeral(ctx, interaction, &content).await
+}
+
+/// Updates whichever notification preferences were passed and shows the current ones.
+async fn notifications(
+    ctx: &Context,
+    interaction: &Interaction,
+    options: &[CommandDataOption],
+) -> Result<()> {
+    let user_id = match interaction.author_id() {
+        Some(user_id) => user_id.get(),
+        None => return Ok(()),
+    };
+
+    if !options.is_empty() {
+        ctx.subscribers.upsert(user_id, |subscriber| {
+            if let Some(dms) = boolean_value(options, "dms") {
+                subscriber.opted_out = !dms;
+            }
+            if let Some(status) = boolean_value(options, "status") {
+                subscriber.status = status;
+            }
+            if let Some(resolution) = boolean_value(options, "resolution") {
+                subscriber.resolution = resolution;
+            }
+            if let Some(comments) = boolean_value(options, "comments") {
+                subscriber.comments = comments;
+            }
+        })?;
+    }
+
+    let content = ctx.subscribers.get(user_id).unwrap_or_default().summary();
+    respond_ephemeral(ctx, interaction, &content).await
+}
+
+/// Opens the send to Jira modal, prefilled from the message the command was used on.
+pub async fn handle_send_to_jira_command(
+    ctx: &Context,
+    interaction: &Interaction,
+    data: &CommandData,
+) -> Result<()> {
+    if !interactions::is_staff(interaction) {
+        return respond_ephemeral(ctx, interaction, "Only staff can send messages to Jira.").await;
+    }
+
+    let message = match data.target_id.zip(data.resolved.as_ref()) {
+        Some((target_id, resolved)) => match resolved.messages.get(&target_id.cast()) {
+            Some(message) => message,
+            None => return Ok(()),
+        },
+        None => return Ok(()),
+    };
+
+    // thread names are capped at 100 characters, so only the first line is used
+    let summary = match message.content.lines().next() {
+        Some(line) if !line.trim().is_empty() => line.trim().chars().take(100).collect(),
+        _ => format!("Bug report from {}", message.author.name),
+    };
+    let description = message.content.chars().take(1800).collect();
+
+    let custom_id = format!(
+        "{}:{}:{}",
+        SEND_TO_JIRA_MODAL_ID, message.channel_id, message.id
+    );
+
+    respond_modal(
+        ctx,
+        interaction,
+        custom_id,
+        "Send to Jira",
+        vec![
+            text_input(
+                "summary",
+                "Summary",
+                TextInputStyle::Short,
+                100,
+                true,
+                Some(summary),
+            ),
+            text_input(
+                "description",
+                "Description",
+                TextInputStyle::Paragraph,
+                1800,
+                false,
+                Some(description),
+            ),
+            text_input(
+                "issue_type",
+                "Issue type",
+                TextInputStyle::Short,
+                50,
+                true,
+                Some("Bug".to_owned()),
+            ),
+        ],
+    )
+    .await
+}
+
+/// Creates a bug report post mirroring the message and its attachments, then syncs it to Jira.
+pub async fn handle_send_to_jira_modal(
+    ctx: &Arc<Context>,
+    interaction: &Interaction,
+    data: &ModalInteractionData,
+    target: &str,
+) -> Result<()> {
+    if !interactions::is_staff(interaction) {
+        return respond_ephemeral(ctx, interaction, "Only staff can send messages to Jira.").await;
+    }
+
+    let (channel_id, message_id) = target
+        .split_once(':')
+        .and_then(|(channel_id, message_id)| {
+            Some((
+                Id::<ChannelMarker>::new_checked(channel_id.parse().ok()?)?,
+                Id::<MessageMarker>::new_checked(message_id.parse().ok()?)?,
+            ))
+        })
+        .ok_or_else(|| Error::Parse(format!("invalid send to jira target: {}", target)))?;
+
+    defer_ephemeral(ctx, interaction).await?;
+
+    let message = ctx.discord.message(channel_id, message_id);
+    let message = metrics::observe(&DISCORD_REQUEST_DURATION, "get_message", message)
+        .await?
+        .model()
+        .await?;
+
+    let summary = modal_value(data, "summary").unwrap_or("Bug report");
+    let description = modal_value(data, "description").unwrap_or_default();
+    let issue_type = modal_value(data, "issue_type");
+
+    let mut attachments = Vec::with_capacity(message.attachments.len());
+    for (index, attachment) in message.attachments.iter().enumerate() {
+        let bytes = utils::download_discord_attachment(ctx, attachment).await?;
+        attachments.push(Attachment::from_bytes(
+            attachment.filename.clone(),
+            bytes,
+            index as u64,
+        ));
+    }
+
+    let content = format!(
+        "{}\n\nSent to Jira from https://discord.com/channels/{}/{}/{} (posted by <@{}>)",
+        description,
+        *constants::GUILD_ID,
+        channel_id,
+        message_id,
+        message.author.id
+    );
+    let tags = [Id::new(utils::sync_tag_id()?)];
+
+    let request = ctx
+        .discord
+        .create_forum_thread(Id::new(*constants::BUG_REPORT_CHANNEL_ID), summary)
+        .applied_tags(&tags)
+        .message()
+        .content(&content)?
+        .attachments(&attachments)?;
+    let post = metrics::observe(&DISCORD_REQUEST_DURATION, "create_forum_thread", request)
+        .await?
+        .model()
+        .await?;
+    let thread_id = post.channel.id.get();
+    ctx.post_reporters
+        .insert(thread_id, message.author.id.get())?;
+
+    tracing::info!(thread_id, "created bug report post from message");
+
+    ctx.jobs.spawn(
+        ctx,
+        SyncJob {
+            issue_type: issue_type.map(str::to_owned),
+            ..SyncJob::new(thread_id)
+        },
+    );
+
+    let content = format!("Created <#{}>, it's being synced to Jira now.", thread_id);
+    update_deferred(ctx, interaction, &content).await
+}
+
+pub async fn handle_report_bug_command(ctx: &Context, interaction: &Interaction) -> Result<()> {
+    respond_modal(
+        ctx,
+        interaction,
+        REPORT_BUG_MODAL_ID.to_owned(),
+        "Report a bug",
+        vec![
+            text_input("summary", "Summary", TextInputStyle::Short, 100, true, None),
+            text_input(
+                "steps",
+                "Steps to reproduce",
+                TextInputStyle::Paragraph,
+                1000,
+                true,
+                None,
+            ),
+            text_input(
+                "expected_actual",
+                "Expected vs actual",
+                TextInputStyle::Paragraph,
+                500,
+                true,
+                None,
+            ),
+            text_input(
+                "platform",
+                "Platform",
+                TextInputStyle::Short,
+                50,
+                true,
+                None,
+            ),
+            text_input(
+                "app_version",
+                "App version",
+                TextInputStyle::Short,
+                50,
+                false,
+                None,
+            ),
+        ],
+    )
+    .await
+}
+
+/// Posts the submitted report in the bug report forum, laid out so the sections can be
+/// picked back up when the post is synced to Jira.
+pub async fn handle_report_bug_modal(
+    ctx: &Context,
+    interaction: &Interaction,
+    data: &ModalInteractionData,
+) -> Result<()> {
+    defer_ephemeral(ctx, interaction).await?;
+
+    let summary = modal_value(data, "summary").unwrap_or("Bug report");
+
+    let author_id = interaction
+        .author_id()
+        .ok_or_else(|| Error::Parse("report submitted without an author".to_owned()))?;
+
+    let report = BugReport {
+        sections: report::SECTIONS
+            .iter()
+            .filter_map(|(key, _)| Some((key.to_string(), modal_value(data, key)?.to_owned())))
+            .collect(),
+        preamble: format!("Reported by <@{}>", author_id),
+    };
+    let content = report.to_message_content();
+
+    let request = ctx
+        .discord
+        .create_forum_thread(Id::new(*constants::BUG_REPORT_CHANNEL_ID), summary)
+        .message()
+        .content(&content)?;
+    let post = metrics::observe(&DISCORD_REQUEST_DURATION, "create_forum_thread", request)
+        .await?
+        .model()
+        .await?;
+    ctx.post_reporters
+        .insert(post.channel.id.get(), author_id.get())?;
+
+    tracing::info!(
+        thread_id = post.channel.id.get(),
+        "created bug report post from form"
+    );
+
+    // structured reports go straight to the review queue instead of waiting for the sync tag
+    if *constants::REVIEW_MODE {
+        review::request_approval(ctx, post.channel.id.get()).await?;
+    }
+
+    let content = format!(
+        "Thanks for the report! It's been posted as <#{}>.",
+        post.channel.id
+    );
+    update_deferred(ctx, interaction, &content).await
+}
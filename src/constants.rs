@@ -1,5 +1,8 @@
+use std::collections::HashMap;
+
 use lazy_static::lazy_static;
 use serde::{Deserialize, Serialize};
+use twilight_model::guild::Permissions;
 
 // lazy static for required env vars
 lazy_static! {
@@ -11,6 +14,163 @@ lazy_static! {
         .unwrap()
         .parse::<u64>()
         .unwrap();
+    /// roles allowed to manage Jira links, comma separated
+    pub static ref STAFF_ROLE_IDS: Vec<u64> = dotenv::var("STAFF_ROLE_IDS")
+        .map(|ids| {
+            ids.split(',')
+                .filter(|id| !id.trim().is_empty())
+                .map(|id| id.trim().parse::<u64>().unwrap())
+                .collect()
+        })
+        .unwrap_or_default();
+    /// channel for notices meant for staff only, falls back to the thread itself when unset
+    pub static ref STAFF_CHANNEL_ID: Option<u64> = dotenv::var("STAFF_CHANNEL_ID")
+        .ok()
+        .map(|id| id.parse::<u64>().unwrap());
+    /// permissions that allow a member without a staff role to apply the sync tag, as permission bits,
+    /// defaults to MANAGE_THREADS
+    pub static ref SYNC_TAG_PERMISSIONS: Permissions = dotenv::var("SYNC_TAG_PERMISSIONS")
+        .map(|bits| Permissions::from_bits_truncate(bits.parse::<u64>().unwrap()))
+        .unwrap_or(Permissions::MANAGE_THREADS);
+    /// when enabled, synced bug reports wait for staff approval in the staff channel before an issue is created
+    pub static ref REVIEW_MODE: bool = dotenv::var("REVIEW_MODE")
+        .map(|enabled| enabled.parse::<bool>().unwrap())
+        .unwrap_or(false);
+    /// jira statuses that mean the reporter has to provide more information, comma separated
+    pub static ref NEEDS_INFO_STATUSES: Vec<String> = dotenv::var("NEEDS_INFO_STATUSES")
+        .unwrap_or_else(|_| "Needs Info,Waiting for Reporter".to_string())
+        .split(',')
+        .map(|status| status.trim().to_owned())
+        .filter(|status| !status.is_empty())
+        .collect();
+    /// jira transition applied to a resolved issue when a reporter says it's still happening, e.g. "Reopen"
+    pub static ref STILL_HAPPENING_TRANSITION: Option<String> =
+        dotenv::var("STILL_HAPPENING_TRANSITION").ok();
+    /// jira transition applied when a reporter says the issue is fixed for them, unset to only leave a comment
+    pub static ref FIXED_TRANSITION: Option<String> = dotenv::var("FIXED_TRANSITION").ok();
+    /// forum tag applied to posts that don't match the bug report template
+    pub static ref NEEDS_INFO_TAG_ID: Option<u64> = dotenv::var("NEEDS_INFO_TAG_ID")
+        .ok()
+        .map(|id| id.parse::<u64>().unwrap());
+    /// headings every bug report has to contain, comma separated
+    pub static ref TEMPLATE_REQUIRED_HEADINGS: Vec<String> = dotenv::var("TEMPLATE_REQUIRED_HEADINGS")
+        .map(|headings| {
+            headings
+                .split(',')
+                .map(|heading| heading.trim().to_owned())
+                .filter(|heading| !heading.is_empty())
+                .collect()
+        })
+        .unwrap_or_default();
+    /// patterns every bug report has to match, semicolon separated `description=regex` pairs,
+    /// e.g. "an app version=\d+\.\d+;a screenshot or video=https?://"
+    pub static ref TEMPLATE_REQUIRED_PATTERNS: Vec<(String, String)> = dotenv::var("TEMPLATE_REQUIRED_PATTERNS")
+        .map(|patterns| {
+            patterns
+                .split(';')
+                .filter_map(|pair| pair.split_once('='))
+                .map(|(name, pattern)| (name.trim().to_owned(), pattern.trim().to_owned()))
+                .collect()
+        })
+        .unwrap_or_default();
+    /// post issue updates as plain text instead of embeds
+    pub static ref PLAIN_TEXT_UPDATES: bool = dotenv::var("PLAIN_TEXT_UPDATES")
+        .map(|enabled| enabled.parse::<bool>().unwrap())
+        .unwrap_or(false);
+    pub static ref JIRA_BASE_URL: String = dotenv::var("JIRA_BASE_URL")
+        .unwrap_or_else(|_| "https://computerlunch.atlassian.net".to_string());
+    /// bug report sections copied into Jira custom fields, comma separated `section=customfield_id`
+    /// pairs, e.g. "platform=customfield_10050,app_version=customfield_10051"
+    pub static ref JIRA_REPORT_FIELDS: HashMap<String, String> = dotenv::var("JIRA_REPORT_FIELDS")
+        .map(|fields| {
+            fields
+                .split(',')
+                .filter_map(|pair| pair.split_once('='))
+                .map(|(section, field)| (section.trim().to_owned(), field.trim().to_owned()))
+                .collect()
+        })
+        .unwrap_or_default();
+    /// custom field the number of users affected by an issue is written to
+    pub static ref AFFECTED_COUNT_FIELD: Option<String> = dotenv::var("AFFECTED_COUNT_FIELD").ok();
+    /// text custom field the list of users affected by an issue is written to
+    pub static ref AFFECTED_USERS_FIELD: Option<String> = dotenv::var("AFFECTED_USERS_FIELD").ok();
+    /// label added to issues while at least one user is affected
+    pub static ref AFFECTED_LABEL: Option<String> = dotenv::var("AFFECTED_LABEL").ok();
+    /// priorities issues are raised to once enough users are affected, comma separated
+    /// `count=priority` pairs, e.g. "5=High,20=Highest"
+    pub static ref PRIORITY_THRESHOLDS: Vec<(usize, String)> = dotenv::var("PRIORITY_THRESHOLDS")
+        .map(|thresholds| {
+            thresholds
+                .split(',')
+                .filter_map(|pair| pair.split_once('='))
+                .map(|(count, priority)| {
+                    (count.trim().parse::<usize>().unwrap(), priority.trim().to_owned())
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    /// projects whose issue keys are unfurled when mentioned in chat, comma separated
+    pub static ref UNFURL_PROJECTS: Vec<String> = dotenv::var("UNFURL_PROJECTS")
+        .unwrap_or_else(|_| "CELLS".to_string())
+        .split(',')
+        .map(|project| project.trim().to_uppercase())
+        .filter(|project| !project.is_empty())
+        .collect();
+    /// channels issue keys are unfurled in unless turned off with `/jira unfurl`, comma separated,
+    /// threads follow their parent channel
+    pub static ref UNFURL_CHANNEL_IDS: Vec<u64> = dotenv::var("UNFURL_CHANNEL_IDS")
+        .map(|ids| {
+            ids.split(',')
+                .filter(|id| !id.trim().is_empty())
+                .map(|id| id.trim().parse::<u64>().unwrap())
+                .collect()
+        })
+        .unwrap_or_default();
+    /// how long the same issue isn't unfurled again in a channel
+    pub static ref UNFURL_COOLDOWN_SECS: u64 = dotenv::var("UNFURL_COOLDOWN_SECS")
+        .map(|cooldown| cooldown.parse::<u64>().unwrap())
+        .unwrap_or(300);
+    /// which way thread names and issue summaries are kept in sync: "both", "discord" or "jira" to
+    /// only copy changes made on that side, or "off"
+    pub static ref TITLE_SYNC: String = dotenv::var("TITLE_SYNC")
+        .map(|mode| mode.to_lowercase())
+        .unwrap_or_else(|_| "both".to_string());
+    /// what happens when a reporter edits their post after it was synced: "replace" swaps out their
+    /// part of the description, "comment" adds a comment with a diff instead, "off" ignores edits
+    pub static ref DESCRIPTION_EDITS: String = dotenv::var("DESCRIPTION_EDITS")
+        .map(|mode| mode.to_lowercase())
+        .unwrap_or_else(|_| "replace".to_string());
+    /// label added to issues whose Discord thread or post was deleted
+    pub static ref DELETED_LABEL: Option<String> = dotenv::var("DELETED_LABEL").ok();
+    /// whether threads of issues marked as duplicates are locked, so the conversation moves to the original
+    pub static ref LOCK_DUPLICATE_THREADS: bool = dotenv::var("LOCK_DUPLICATE_THREADS")
+        .map(|enabled| enabled.parse::<bool>().unwrap())
+        .unwrap_or(false);
+    /// how many possibly matching issues new bug reports are pointed at, 0 turns the suggestions off
+    pub static ref SIMILAR_ISSUES_LIMIT: usize = dotenv::var("SIMILAR_ISSUES_LIMIT")
+        .map(|limit| limit.parse::<usize>().unwrap())
+        .unwrap_or(3);
+    /// how alike a previously synced thread has to be to count as a match, between 0 and 1
+    pub static ref SIMILARITY_THRESHOLD: f64 = dotenv::var("SIMILARITY_THRESHOLD")
+        .map(|threshold| threshold.parse::<f64>().unwrap())
+        .unwrap_or(0.3);
+    /// extra JQL the text search for matching issues is narrowed down with, e.g. "statusCategory != Done"
+    pub static ref SIMILAR_ISSUES_JQL: Option<String> = dotenv::var("SIMILAR_ISSUES_JQL").ok();
+    pub static ref JIRA_USERNAME: String = dotenv::var("JIRA_USERNAME").unwrap();
+    pub static ref JIRA_TOKEN: String = dotenv::var("JIRA_TOKEN").unwrap();
+    /// either "json" or "pretty"
+    pub static ref LOG_FORMAT: String =
+        dotenv::var("LOG_FORMAT").unwrap_or_else(|_| "pretty".to_string());
+    pub static ref DATA_DIR: String =
+        dotenv::var("DATA_DIR").unwrap_or_else(|_| "data".to_string());
+    pub static ref PENDING_SYNCS_PATH: String = format!("{}/pending_syncs.json", *DATA_DIR);
+    /// readiness fails once more sync jobs than this are waiting to finish
+    pub static ref READY_MAX_QUEUE_DEPTH: usize = dotenv::var("READY_MAX_QUEUE_DEPTH")
+        .map(|depth| depth.parse::<usize>().unwrap())
+        .unwrap_or(25);
+    pub static ref SHUTDOWN_TIMEOUT_SECS: u64 = dotenv::var("SHUTDOWN_TIMEOUT_SECS")
+        .map(|timeout| timeout.parse::<u64>().unwrap())
+        .unwrap_or(30);
 }
 
 #[derive(Debug, Deserialize, Serialize)]
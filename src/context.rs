@@ -0,0 +1,79 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Mutex, OnceLock, RwLock},
+};
+
+use twilight_cache_inmemory::InMemoryCache;
+use twilight_http::Client as HttpClient;
+
+use crate::{
+    constants,
+    error::Result,
+    gateway::GatewayStatus,
+    jira::JiraClient,
+    jobs::SyncJobs,
+    review::PendingReview,
+    storage::{JsonMap, LinkStore},
+    subscriptions::{self, Subscriber},
+};
+
+/// State shared by the gateway handlers and the webhook server.
+///
+/// There is exactly one of these per process so every Discord request goes through the
+/// same ratelimiter and every outgoing HTTP request shares one connection pool.
+pub struct Context {
+    pub discord: HttpClient,
+    pub jira: JiraClient,
+    /// client for anything that isn't Jira, e.g. downloading Discord attachments
+    pub http: reqwest::Client,
+    pub cache: InMemoryCache,
+    pub jobs: SyncJobs,
+    pub links: LinkStore,
+    /// bug report threads a moderator allowed to sync without matching the template, thread id -> moderator id
+    pub template_overrides: JsonMap<u64>,
+    /// bug reports waiting for staff approval, by thread id
+    pub reviews: JsonMap<PendingReview>,
+    /// who a bug report post the bot created was reported by, thread id -> user id
+    pub post_reporters: JsonMap<u64>,
+    /// issue subscriptions and notification preferences, by user id
+    pub subscribers: JsonMap<Subscriber>,
+    /// channels issue keys are or aren't unfurled in, overriding the configured ones
+    pub unfurl_channels: JsonMap<bool>,
+    /// when an issue was last unfurled in a channel, by `(channel id, issue key)`
+    pub unfurl_cooldowns: Mutex<HashMap<(u64, String), u64>>,
+    /// interactions that were deferred and are still being handled, by interaction id
+    pub deferred_interactions: Mutex<HashSet<u64>>,
+    pub gateway_status: RwLock<GatewayStatus>,
+    /// the bot's own user id, known once the gateway is ready
+    pub bot_user_id: OnceLock<u64>,
+}
+
+impl Context {
+    pub fn new() -> Result<Self> {
+        let http = reqwest::Client::new();
+
+        let ctx = Context {
+            discord: HttpClient::new(constants::DISCORD_TOKEN.to_string()),
+            jira: JiraClient::new(http.clone()),
+            http,
+            cache: InMemoryCache::builder()
+                .resource_types(twilight_cache_inmemory::ResourceType::CHANNEL)
+                .build(),
+            jobs: SyncJobs::default(),
+            links: LinkStore::load()?,
+            template_overrides: JsonMap::load("template_overrides.json")?,
+            reviews: JsonMap::load("pending_reviews.json")?,
+            post_reporters: JsonMap::load("post_reporters.json")?,
+            subscribers: JsonMap::load("subscribers.json")?,
+            unfurl_channels: JsonMap::load("unfurl_channels.json")?,
+            unfurl_cooldowns: Mutex::new(HashMap::new()),
+            deferred_interactions: Mutex::new(HashSet::new()),
+            gateway_status: RwLock::new(GatewayStatus::default()),
+            bot_user_id: OnceLock::new(),
+        };
+
+        subscriptions::migrate_link_subscribers(&ctx)?;
+
+        Ok(ctx)
+    }
+}
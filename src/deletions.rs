@@ -0,0 +1,146 @@
+use reqwest::{multipart, Method};
+use serde_json::json;
+use twilight_model::{
+    gateway::payload::incoming::{MessageDelete, ThreadDelete},
+    id::Id,
+};
+
+use crate::{
+    constants,
+    context::Context,
+    error::{Error, Result},
+    metrics::{self, ATTACHMENTS_UPLOADED, DISCORD_REQUEST_DURATION},
+    utils,
+};
+
+/// Records a linked thread being deleted on its issue.
+pub async fn thread_deleted(ctx: &Context, thread: &ThreadDelete) -> Result<()> {
+    ctx.post_reporters.remove(thread.id.get())?;
+
+    orphan_link(
+        ctx,
+        thread.id.get(),
+        "The Discord thread for this issue was deleted.",
+    )
+    .await
+}
+
+/// Records the starter message of a linked thread being deleted on its issue, along with
+/// a transcript of what's left of the thread.
+pub async fn starter_message_deleted(ctx: &Context, message: &MessageDelete) -> Result<()> {
+    let thread_id = message.channel_id.get();
+    if !utils::is_starter_message(message.id.get(), thread_id) {
+        return Ok(());
+    }
+
+    let link = match ctx.links.get(thread_id) {
+        Some(link) if !link.orphaned => link,
+        _ => return Ok(()),
+    };
+
+    // the rest of the thread is still around, a transcript is better than nothing
+    if let Err(error) = attach_transcript(ctx, thread_id, &link.issue_key).await {
+        tracing::warn!(%error, thread_id, "couldn't capture thread transcript");
+    }
+
+    orphan_link(
+        ctx,
+        thread_id,
+        "The Discord post for this issue was deleted, its thread is no longer updated.",
+    )
+    .await
+}
+
+/// Marks the thread's link as orphaned so updates stop being posted there, and notes why on the issue.
+async fn orphan_link(ctx: &Context, thread_id: u64, comment: &str) -> Result<()> {
+    let link = match ctx.links.get(thread_id) {
+        Some(link) => link,
+        None => return Ok(()),
+    };
+    let already_orphaned = link.orphaned;
+
+    ctx.links.update(thread_id, |link| link.orphaned = true)?;
+
+    let comment = format!("{}\nIt was at {}", comment, utils::thread_url(thread_id));
+    utils::add_jira_comment(ctx, &link.issue_key, &comment).await?;
+
+    // the issue may still be followed in other threads
+    let all_orphaned = ctx
+        .links
+        .find_all_by_issue(&link.issue_key)
+        .iter()
+        .all(|link| link.orphaned);
+    if let Some(label) = constants::DELETED_LABEL
+        .as_ref()
+        .filter(|_| !already_orphaned && all_orphaned)
+    {
+        utils::update_jira_issue(
+            ctx,
+            &link.issue_key,
+            &json!({ "update": { "labels": [{ "add": label }] } }),
+        )
+        .await?;
+    }
+
+    tracing::info!(thread_id, jira_key = %link.issue_key, "orphaned link to deleted thread");
+
+    Ok(())
+}
+
+/// Uploads the messages left in the thread to the issue as a text file.
+async fn attach_transcript(ctx: &Context, thread_id: u64, issue_key: &str) -> Result<()> {
+    let request = ctx
+        .discord
+        .channel_messages(Id::new(thread_id))
+        .limit(100)?;
+    let mut messages = metrics::observe(&DISCORD_REQUEST_DURATION, "get_messages", request)
+        .await?
+        .models()
+        .await?;
+
+    if messages.is_empty() {
+        return Ok(());
+    }
+
+    // discord returns the newest messages first
+    messages.reverse();
+
+    let transcript = messages
+        .iter()
+        .map(|message| {
+            let mut line = format!(
+                "[{}] {}: {}",
+                message.timestamp.iso_8601(),
+                message.author.name,
+                message.content
+            );
+            for attachment in &message.attachments {
+                line.push_str(&format!("\n    attachment: {}", attachment.url));
+            }
+            line
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let part = multipart::Part::bytes(transcript.into_bytes())
+        .file_name(format!("discord-transcript-{}.txt", thread_id))
+        .mime_str("text/plain")
+        .map_err(|error| Error::Parse(error.to_string()))?;
+    let request = ctx
+        .jira
+        .request(Method::POST, &format!("issue/{}/attachments", issue_key))
+        .multipart(multipart::Form::new().part("file", part))
+        .header("X-Atlassian-Token", "no-check");
+    ctx.jira.send("add_attachment", request).await?;
+
+    ATTACHMENTS_UPLOADED.inc();
+
+    tracing::info!(
+        thread_id,
+        jira_key = issue_key,
+        count = messages.len(),
+        "attached thread transcript"
+    );
+
+    Ok(())
+}
@@ -0,0 +1,145 @@
+use twilight_model::id::Id;
+
+use crate::{
+    constants::{self, Resolutions},
+    context::Context,
+    error::Result,
+    metrics::{self, DISCORD_REQUEST_DURATION},
+    models::{JiraChangelogItem, JiraIssueDetails, JiraWebhookIssueLink},
+    subscriptions, utils,
+};
+
+/// how the duplicate link type describes the link from the duplicate to the original issue
+const DUPLICATES: &str = "duplicates";
+
+/// Whether the changelog resolves the issue as a duplicate.
+pub fn resolved_as_duplicate(items: &[JiraChangelogItem]) -> bool {
+    let duplicate_id = (Resolutions::Duplicate as u32).to_string();
+
+    items.iter().any(|item| {
+        item.field == "resolution"
+            && (item.to_id.as_deref() == Some(duplicate_id.as_str())
+                || item.to.as_deref() == Some("Duplicate"))
+    })
+}
+
+/// Points the threads of an issue resolved as a duplicate at the issue it duplicates, going
+/// by the issue's duplicate link.
+pub async fn issue_resolved_as_duplicate(ctx: &Context, issue: &JiraIssueDetails) -> Result<()> {
+    // webhooks don't always include the issue links
+    let issue = utils::get_jira_issue(ctx, &issue.key).await?;
+
+    let original_key = issue
+        .fields
+        .issuelinks
+        .iter()
+        .filter(|link| link.link_type.outward.eq_ignore_ascii_case(DUPLICATES))
+        .find_map(|link| link.outward_issue.as_ref())
+        .map(|original| original.key.as_str());
+
+    match original_key {
+        Some(original_key) => mark_duplicate(ctx, &issue.key, original_key).await,
+        None => Ok(()),
+    }
+}
+
+/// Points the threads of an issue that was linked as a duplicate at the issue it duplicates.
+pub async fn issue_link_created(ctx: &Context, link: &JiraWebhookIssueLink) -> Result<()> {
+    if !link.link_type.outward_name.eq_ignore_ascii_case(DUPLICATES) {
+        return Ok(());
+    }
+
+    // the webhook only has the issue ids, the links are by key
+    let duplicate = utils::get_jira_issue(ctx, &link.source_issue_id.to_string()).await?;
+    let original = utils::get_jira_issue(ctx, &link.destination_issue_id.to_string()).await?;
+
+    mark_duplicate(ctx, &duplicate.key, &original.key).await
+}
+
+/// Tells the duplicate's threads where the original is discussed, subscribes their reporters
+/// and the issue's subscribers to the original issue, and optionally locks the threads.
+async fn mark_duplicate(ctx: &Context, duplicate_key: &str, original_key: &str) -> Result<()> {
+    // resolving as duplicate usually comes with a duplicate link, only the first one counts
+    let links = ctx
+        .links
+        .find_all_by_issue(duplicate_key)
+        .into_iter()
+        .filter(|link| !link.orphaned && link.duplicate_of.as_deref() != Some(original_key))
+        .collect::<Vec<_>>();
+    if links.is_empty() {
+        return Ok(());
+    }
+
+    let mut user_ids = links
+        .iter()
+        .filter_map(|link| link.reporter_id)
+        .collect::<Vec<_>>();
+    user_ids.extend(
+        ctx.subscribers
+            .entries()
+            .into_iter()
+            .filter(|(_, subscriber)| subscriber.issue_keys.iter().any(|key| key == duplicate_key))
+            .map(|(user_id, _)| user_id),
+    );
+    for user_id in user_ids {
+        subscriptions::subscribe(ctx, user_id, original_key)?;
+    }
+
+    let original_thread_id = ctx
+        .links
+        .find_by_issue(original_key)
+        .filter(|original| !original.orphaned)
+        .map(|original| original.thread_id);
+    let follow = match original_thread_id {
+        Some(original_thread_id) => format!("please follow along in <#{}>", original_thread_id),
+        None => format!(
+            "please follow along at <{}>",
+            utils::issue_url(original_key)
+        ),
+    };
+
+    for link in links {
+        let thread_id = link.thread_id;
+
+        ctx.links.update(thread_id, |link| {
+            link.duplicate_of = Some(original_key.to_owned())
+        })?;
+
+        let mut message = format!(
+            "This report is a duplicate of {}, {}.",
+            original_key, follow
+        );
+        if let Some(reporter_id) = link.reporter_id {
+            message = format!("<@{}> {}", reporter_id, message);
+
+            // subscriptions to issues without a thread don't send DMs
+            let gets_dms = original_thread_id.is_some()
+                && ctx
+                    .subscribers
+                    .get(reporter_id)
+                    .unwrap_or_default()
+                    .gets_dms();
+            if gets_dms {
+                message.push_str(&format!(
+                    " You'll get a DM whenever {} changes.",
+                    original_key
+                ));
+            }
+        }
+        utils::send_update_to_user_report(ctx, thread_id, &message).await?;
+
+        if *constants::LOCK_DUPLICATE_THREADS {
+            let request = ctx.discord.update_thread(Id::new(thread_id)).locked(true);
+            metrics::observe(&DISCORD_REQUEST_DURATION, "update_thread", request).await?;
+        }
+
+        tracing::info!(
+            thread_id,
+            jira_key = duplicate_key,
+            original_key,
+            "marked thread as duplicate"
+        );
+    }
+
+    Ok(())
+}
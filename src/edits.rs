@@ -0,0 +1,207 @@
+use twilight_model::gateway::payload::incoming::MessageUpdate;
+
+use crate::{
+    constants,
+    context::Context,
+    error::Result,
+    utils::{self, REPORT_ORIGIN},
+};
+
+/// heading in issue descriptions that separates the reporter's post from notes added in Jira,
+/// everything from it on is kept when the post is edited
+pub const DEVELOPER_NOTES_HEADING: &str = "h3. Developer notes";
+
+/// Lines removed from `old` prefixed with "- " and lines added in `new` prefixed with "+ ".
+fn line_diff(old: &str, new: &str) -> String {
+    let old = old.lines().collect::<Vec<_>>();
+    let new = new.lines().collect::<Vec<_>>();
+
+    // longest common subsequence of lines, lengths[i][j] being the lcs of old[i..] and new[j..]
+    let mut lengths = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i][j] = if old[i] == new[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let mut diff = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            i += 1;
+            j += 1;
+        } else if j < new.len() && (i == old.len() || lengths[i][j + 1] >= lengths[i + 1][j]) {
+            diff.push(format!("+ {}", new[j]));
+            j += 1;
+        } else {
+            diff.push(format!("- {}", old[i]));
+            i += 1;
+        }
+    }
+
+    diff.join("\n")
+}
+
+/// Where the reporter's part of an issue description is: from after the report origin line up
+/// to the developer notes heading, if there is one. Descriptions without a report origin
+/// weren't written by the bot, so they have no reporter's part.
+fn reporter_section(description: &str) -> Option<(usize, Option<usize>)> {
+    let origin = description.find(REPORT_ORIGIN)?;
+    let start = description[origin..]
+        .find('\n')
+        .map_or(description.len(), |end| origin + end);
+    let end = description[start..]
+        .find(DEVELOPER_NOTES_HEADING)
+        .map(|end| start + end);
+
+    Some((start, end))
+}
+
+/// Updates the issue description after the reporter edited the starter message of a linked thread.
+///
+/// With `DESCRIPTION_EDITS` set to "replace", the reporter's part of the description is swapped
+/// out, keeping the report origin and the developer notes. Edits get a comment with a diff
+/// instead in "comment" mode, and whenever the reporter's part was changed by staff or can't
+/// be told apart from the rest of the description.
+pub async fn starter_message_edited(ctx: &Context, update: &MessageUpdate) -> Result<()> {
+    if !utils::is_starter_message(update.id.get(), update.channel_id.get())
+        || constants::DESCRIPTION_EDITS.as_str() == "off"
+    {
+        return Ok(());
+    }
+
+    // the description is the primary thread's report, followers' edits don't belong in it
+    let (link, content) = match ctx
+        .links
+        .get(update.id.get())
+        .zip(update.content.as_deref())
+    {
+        Some((link, content)) if link.follows.is_none() => (link, content),
+        _ => return Ok(()),
+    };
+
+    let issue = utils::get_jira_issue(ctx, &link.issue_key).await?;
+    let description = issue
+        .fields
+        .description
+        .unwrap_or_default()
+        .replace("\r\n", "\n");
+    let section = reporter_section(&description);
+    let current =
+        section.map(|(start, end)| description[start..end.unwrap_or(description.len())].trim());
+
+    // links from before descriptions were stored can only go by the issue
+    let old = match (link.description.as_deref(), current) {
+        (Some(old), _) | (None, Some(old)) => old.trim().to_owned(),
+        (None, None) => return Ok(()),
+    };
+    let new = utils::report_description(content);
+    let new = new.trim();
+
+    if old == new {
+        return Ok(());
+    }
+
+    ctx.links.update(link.thread_id, |link| {
+        link.description = Some(new.to_owned())
+    })?;
+
+    let replace = constants::DESCRIPTION_EDITS.as_str() == "replace"
+        && !link.description_reviewed
+        && current == Some(old.as_str());
+
+    match section {
+        Some((start, Some(end))) if replace => {
+            let description = format!(
+                "{}\n\n{}\n\n{}",
+                &description[..start],
+                new,
+                &description[end..]
+            );
+            utils::update_jira_issue(
+                ctx,
+                &link.issue_key,
+                &serde_json::json!({ "fields": { "description": description } }),
+            )
+            .await?;
+
+            tracing::info!(thread_id = link.thread_id, jira_key = %link.issue_key, "updated description after edit");
+        }
+        _ => {
+            let author = update
+                .author
+                .as_ref()
+                .map_or("The reporter", |author| author.name.as_str());
+            let comment = format!(
+                "{} edited their report on Discord:\n{{noformat}}\n{}\n{{noformat}}",
+                author,
+                line_diff(&old, new)
+            );
+            utils::add_jira_comment(ctx, &link.issue_key, &comment).await?;
+
+            tracing::info!(thread_id = link.thread_id, jira_key = %link.issue_key, "commented on edit");
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diff_marks_added_and_removed_lines() {
+        assert_eq!(
+            line_diff("steps\ncrashes\nandroid", "steps\nfreezes\nandroid\nv1.2"),
+            "+ freezes\n- crashes\n+ v1.2"
+        );
+    }
+
+    #[test]
+    fn diff_of_equal_text_is_empty() {
+        assert_eq!(line_diff("a\nb", "a\nb"), "");
+    }
+
+    #[test]
+    fn diff_from_nothing_adds_everything() {
+        assert_eq!(line_diff("", "a\nb"), "+ a\n+ b");
+        assert_eq!(line_diff("a\nb", ""), "- a\n- b");
+    }
+
+    #[test]
+    fn section_between_origin_and_notes() {
+        let description = format!(
+            "{}https://discord.com/channels/1/2/2\n\nIt crashes\n\n{}\nlooking into it",
+            REPORT_ORIGIN, DEVELOPER_NOTES_HEADING
+        );
+        let (start, end) = reporter_section(&description).unwrap();
+
+        assert_eq!(description[start..end.unwrap()].trim(), "It crashes");
+        assert!(description[end.unwrap()..].starts_with(DEVELOPER_NOTES_HEADING));
+    }
+
+    #[test]
+    fn section_without_notes_runs_to_the_end() {
+        let description = format!(
+            "{}https://discord.com/channels/1/2/2\n\nIt crashes",
+            REPORT_ORIGIN
+        );
+        let (start, end) = reporter_section(&description).unwrap();
+
+        assert_eq!(end, None);
+        assert_eq!(description[start..].trim(), "It crashes");
+    }
+
+    #[test]
+    fn descriptions_without_origin_have_no_section() {
+        assert_eq!(
+            reporter_section("Written by a developer\n\nh3. Developer notes"),
+            None
+        );
+    }
+}
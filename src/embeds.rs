@@ -0,0 +1,246 @@
+use twilight_model::{
+    channel::message::{
+        embed::{EmbedField, EmbedFooter},
+        Embed,
+    },
+    util::Timestamp,
+};
+
+use crate::{
+    gateway::unix_now,
+    models::{JiraIssueDetails, StatusCategory},
+    utils,
+};
+
+/// color for issues without a status category
+const DEFAULT_COLOR: u32 = 0x6b778c;
+
+/// Embed color for a Jira status category, matching the lozenge colors Jira uses.
+pub fn status_color(category: &StatusCategory) -> u32 {
+    match category.color_name.as_str() {
+        "blue-gray" => 0x42526e,
+        "yellow" => 0xffc400,
+        "green" => 0x36b37e,
+        "blue" => 0x0052cc,
+        _ => DEFAULT_COLOR,
+    }
+}
+
+fn field(name: &str, value: String) -> EmbedField {
+    EmbedField {
+        inline: true,
+        name: name.to_owned(),
+        value,
+    }
+}
+
+/// Status, assignee, priority, resolution and fix versions of the issue, as `(name, value)`.
+fn issue_fields(issue: &JiraIssueDetails) -> [(&'static str, String); 5] {
+    let fields = &issue.fields;
+
+    let fix_versions = fields
+        .fix_versions
+        .iter()
+        .map(|version| version.name.as_str())
+        .collect::<Vec<_>>();
+
+    [
+        (
+            "Status",
+            fields
+                .status
+                .as_ref()
+                .map_or("Unknown".to_owned(), |status| status.name.clone()),
+        ),
+        (
+            "Assignee",
+            fields
+                .assignee
+                .as_ref()
+                .map_or("Unassigned".to_owned(), |assignee| {
+                    assignee.display_name.clone()
+                }),
+        ),
+        (
+            "Priority",
+            fields
+                .priority
+                .as_ref()
+                .map_or("None".to_owned(), |priority| priority.name.clone()),
+        ),
+        (
+            "Resolution",
+            fields
+                .resolution
+                .as_ref()
+                .map_or("Unresolved".to_owned(), |resolution| {
+                    resolution.name.clone()
+                }),
+        ),
+        (
+            "Fix version",
+            if fix_versions.is_empty() {
+                "None".to_owned()
+            } else {
+                fix_versions.join(", ")
+            },
+        ),
+    ]
+}
+
+/// Parses a Jira timestamp, whose offset has no colon unlike the RFC 3339 ones Discord uses.
+fn parse_jira_timestamp(timestamp: &str) -> Option<Timestamp> {
+    let split = timestamp.len().checked_sub(5)?;
+    let (datetime, offset) = (timestamp.get(..split)?, timestamp.get(split..)?);
+    let sign = match offset.as_bytes()[0] {
+        b'+' => 1,
+        b'-' => -1,
+        _ => return None,
+    };
+    let hours = offset.get(1..3)?.parse::<i64>().ok()?;
+    let minutes = offset.get(3..5)?.parse::<i64>().ok()?;
+
+    // twilight keeps the local time and drops the offset, so it's parsed as utc and shifted after
+    let local = Timestamp::parse(&format!("{}+00:00", datetime)).ok()?;
+    Timestamp::from_secs(local.as_secs() - sign * (hours * 3600 + minutes * 60)).ok()
+}
+
+/// Embed summarizing the current state of an issue, with whoever changed it in the footer
+/// and the time of the change as its timestamp.
+pub fn issue_embed(issue: &JiraIssueDetails, changed_by: Option<&str>) -> Embed {
+    Embed {
+        author: None,
+        color: Some(issue_color(issue)),
+        description: None,
+        fields: issue_fields(issue)
+            .into_iter()
+            .map(|(name, value)| field(name, value))
+            .collect(),
+        footer: changed_by.map(|changed_by| EmbedFooter {
+            icon_url: None,
+            proxy_icon_url: None,
+            text: format!("Changed by {}", changed_by),
+        }),
+        image: None,
+        kind: "rich".to_owned(),
+        provider: None,
+        thumbnail: None,
+        timestamp: issue
+            .fields
+            .updated
+            .as_deref()
+            .and_then(parse_jira_timestamp)
+            .or_else(|| Timestamp::from_secs(unix_now() as i64).ok()),
+        title: Some(issue_title(issue)),
+        url: Some(utils::issue_url(&issue.key)),
+        video: None,
+    }
+}
+
+fn issue_color(issue: &JiraIssueDetails) -> u32 {
+    issue
+        .fields
+        .status
+        .as_ref()
+        .and_then(|status| status.status_category.as_ref())
+        .map_or(DEFAULT_COLOR, status_color)
+}
+
+fn issue_title(issue: &JiraIssueDetails) -> String {
+    // embed titles are capped at 256 characters
+    format!("{}: {}", issue.key, issue.fields.summary)
+        .chars()
+        .take(256)
+        .collect()
+}
+
+/// Smaller version of [`issue_embed`] for issues mentioned in chat, with just the status,
+/// assignee and the Discord thread the issue is linked to.
+pub fn compact_issue_embed(issue: &JiraIssueDetails, thread_id: Option<u64>) -> Embed {
+    let [status, assignee, ..] = issue_fields(issue);
+
+    let mut fields = vec![field(status.0, status.1), field(assignee.0, assignee.1)];
+    if let Some(thread_id) = thread_id {
+        fields.push(field("Thread", format!("<#{}>", thread_id)));
+    }
+
+    Embed {
+        author: None,
+        color: Some(issue_color(issue)),
+        description: None,
+        fields,
+        footer: None,
+        image: None,
+        kind: "rich".to_owned(),
+        provider: None,
+        thumbnail: None,
+        timestamp: None,
+        title: Some(issue_title(issue)),
+        url: Some(utils::issue_url(&issue.key)),
+        video: None,
+    }
+}
+
+/// Plain text version of [`issue_embed`], for when embeds are turned off.
+pub fn issue_text(issue: &JiraIssueDetails, changed_by: Option<&str>) -> String {
+    let mut text = format!(
+        "**{}**: {}\n<{}>",
+        issue.key,
+        issue.fields.summary,
+        utils::issue_url(&issue.key)
+    );
+
+    for (name, value) in issue_fields(issue) {
+        text.push_str(&format!("\n{}: {}", name, value));
+    }
+
+    if let Some(changed_by) = changed_by {
+        text.push_str(&format!("\nChanged by {}", changed_by));
+    }
+
+    text
+}
+
+/// Plain text version of [`compact_issue_embed`].
+pub fn compact_issue_text(issue: &JiraIssueDetails, thread_id: Option<u64>) -> String {
+    let [status, assignee, ..] = issue_fields(issue);
+
+    let mut text = format!(
+        "**{}**: {} ({}, {})\n<{}>",
+        issue.key,
+        issue.fields.summary,
+        status.1,
+        assignee.1,
+        utils::issue_url(&issue.key)
+    );
+
+    if let Some(thread_id) = thread_id {
+        text.push_str(&format!("\nThread: <#{}>", thread_id));
+    }
+
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_jira_timestamps() {
+        let utc = parse_jira_timestamp("2021-08-10T11:16:37.020+0000").unwrap();
+        assert_eq!(utc.as_secs(), 1_628_594_197);
+
+        let ahead = parse_jira_timestamp("2021-08-10T13:16:37.020+0200").unwrap();
+        assert_eq!(ahead.as_secs(), 1_628_594_197);
+
+        let behind = parse_jira_timestamp("2021-08-10T06:46:37.020-0430").unwrap();
+        assert_eq!(behind.as_secs(), 1_628_594_197);
+    }
+
+    #[test]
+    fn rejects_other_timestamps() {
+        assert!(parse_jira_timestamp("2021-08-10T11:16:37Z").is_none());
+        assert!(parse_jira_timestamp("yesterday").is_none());
+        assert!(parse_jira_timestamp("").is_none());
+    }
+}
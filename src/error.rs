@@ -0,0 +1,233 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+use reqwest::StatusCode;
+use serde::Deserialize;
+use twilight_http::{error::ErrorType, response::DeserializeBodyError};
+use twilight_validate::{
+    channel::ChannelValidationError, message::MessageValidationError, request::ValidationError,
+};
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+#[derive(Debug)]
+pub enum Error {
+    /// a request to the Discord API failed
+    DiscordHttp(Box<twilight_http::Error>),
+    /// a request to the Jira API failed, either before getting a response (`status` is `None`)
+    /// or with an error status and whatever error messages Jira sent back
+    JiraHttp {
+        status: Option<StatusCode>,
+        messages: Vec<String>,
+        source: Option<reqwest::Error>,
+    },
+    /// a file couldn't be downloaded, e.g. a Discord attachment
+    Download(reqwest::Error),
+    /// a required setting is missing or invalid
+    Config(String),
+    /// a payload couldn't be built or understood
+    Parse(String),
+    Storage(std::io::Error),
+}
+
+/// Error body returned by the Jira API.
+///
+/// <https://developer.atlassian.com/cloud/jira/platform/rest/v2/intro/#status-codes>
+#[derive(Debug, Default, Deserialize)]
+struct JiraErrorBody {
+    #[serde(rename = "errorMessages", default)]
+    error_messages: Vec<String>,
+    /// field name -> error message
+    #[serde(default)]
+    errors: std::collections::HashMap<String, String>,
+}
+
+impl Error {
+    /// Short label for the kind of error, used for metrics.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Error::DiscordHttp(_) => "discord_http",
+            Error::JiraHttp { .. } => "jira_http",
+            Error::Download(_) => "download",
+            Error::Config(_) => "config",
+            Error::Parse(_) => "parse",
+            Error::Storage(_) => "storage",
+        }
+    }
+
+    /// Status code of the failed request, if it got far enough to receive one.
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            Error::DiscordHttp(error) => match error.kind() {
+                ErrorType::Response { status, .. } => Some(status.get()),
+                ErrorType::Unauthorized => Some(401),
+                ErrorType::ServiceUnavailable { .. } => Some(503),
+                _ => None,
+            },
+            Error::JiraHttp { status, .. } => status.map(|status| status.as_u16()),
+            Error::Download(error) => error.status().map(|status| status.as_u16()),
+            _ => None,
+        }
+    }
+
+    pub fn is_not_found(&self) -> bool {
+        self.status() == Some(404)
+    }
+
+    /// Whether trying the same request again later could succeed, i.e. network
+    /// failures, ratelimits and server errors.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Error::DiscordHttp(error) => match error.kind() {
+                ErrorType::RequestError
+                | ErrorType::RequestTimedOut
+                | ErrorType::ChunkingResponse
+                | ErrorType::ServiceUnavailable { .. } => true,
+                ErrorType::Response { status, .. } => {
+                    status.get() == 429 || status.is_server_error()
+                }
+                _ => false,
+            },
+            Error::JiraHttp { status, source, .. } => match status {
+                Some(status) => {
+                    *status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+                }
+                None => source
+                    .as_ref()
+                    .is_some_and(|source| source.is_connect() || source.is_timeout()),
+            },
+            Error::Download(error) => error.is_connect() || error.is_timeout(),
+            _ => false,
+        }
+    }
+
+    /// Turns an unsuccessful Jira response into an [`Error::JiraHttp`] carrying the
+    /// error messages from the response body.
+    pub async fn from_jira_response(response: reqwest::Response) -> Result<reqwest::Response> {
+        let status = response.status();
+
+        if status.is_success() {
+            return Ok(response);
+        }
+
+        let body = response.json::<JiraErrorBody>().await.unwrap_or_default();
+
+        let messages = body
+            .error_messages
+            .into_iter()
+            .chain(
+                body.errors
+                    .into_iter()
+                    .map(|(field, message)| format!("{}: {}", field, message)),
+            )
+            .collect::<Vec<_>>();
+
+        tracing::warn!(%status, ?messages, "jira request failed");
+
+        Err(Error::JiraHttp {
+            status: Some(status),
+            messages,
+            source: None,
+        })
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Error::DiscordHttp(error) => write!(f, "discord request failed: {}", error),
+            Error::JiraHttp {
+                status,
+                messages,
+                source,
+            } => {
+                f.write_str("jira request failed")?;
+
+                if let Some(status) = status {
+                    write!(f, " ({})", status)?;
+                }
+
+                if let Some(source) = source {
+                    write!(f, ": {}", source)?;
+                }
+
+                if !messages.is_empty() {
+                    write!(f, ": {}", messages.join("; "))?;
+                }
+
+                Ok(())
+            }
+            Error::Download(error) => write!(f, "download failed: {}", error),
+            Error::Config(message) => write!(f, "invalid configuration: {}", message),
+            Error::Parse(message) => write!(f, "parsing failed: {}", message),
+            Error::Storage(error) => write!(f, "storage failed: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::DiscordHttp(error) => Some(error.as_ref()),
+            Error::JiraHttp {
+                source: Some(source),
+                ..
+            } => Some(source),
+            Error::Download(error) => Some(error),
+            Error::Storage(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<twilight_http::Error> for Error {
+    fn from(error: twilight_http::Error) -> Self {
+        Error::DiscordHttp(Box::new(error))
+    }
+}
+
+impl From<DeserializeBodyError> for Error {
+    fn from(error: DeserializeBodyError) -> Self {
+        Error::Parse(error.to_string())
+    }
+}
+
+impl From<MessageValidationError> for Error {
+    fn from(error: MessageValidationError) -> Self {
+        Error::Parse(error.to_string())
+    }
+}
+
+impl From<ChannelValidationError> for Error {
+    fn from(error: ChannelValidationError) -> Self {
+        Error::Parse(error.to_string())
+    }
+}
+
+impl From<ValidationError> for Error {
+    fn from(error: ValidationError) -> Self {
+        Error::Parse(error.to_string())
+    }
+}
+
+/// Only requests to Jira go through reqwest directly, anything else maps its errors explicitly.
+impl From<reqwest::Error> for Error {
+    fn from(error: reqwest::Error) -> Self {
+        Error::JiraHttp {
+            status: error.status(),
+            messages: Vec::new(),
+            source: Some(error),
+        }
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(error: serde_json::Error) -> Self {
+        Error::Parse(error.to_string())
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Self {
+        Error::Storage(error)
+    }
+}
@@ -0,0 +1,181 @@
+use twilight_model::{
+    application::interaction::{modal::ModalInteractionData, Interaction},
+    channel::message::{
+        component::{ActionRow, ButtonStyle, TextInputStyle},
+        Component,
+    },
+};
+
+use crate::{
+    constants,
+    context::Context,
+    error::{Error, Result},
+    interactions::{self, respond_ephemeral, respond_modal, text_input},
+    subscriptions, utils,
+};
+
+/// prefix of the feedback buttons' custom ids, followed by the action and thread id
+pub const FEEDBACK_BUTTON_ID: &str = "feedback";
+/// prefix of the "Add more info" modal's custom id, followed by the thread id
+pub const MORE_INFO_MODAL_ID: &str = "more_info";
+
+/// Buttons for reporters to respond to updates about their issue.
+pub fn feedback_buttons(thread_id: u64) -> Vec<Component> {
+    let custom_id = |action: &str| format!("{}:{}:{}", FEEDBACK_BUTTON_ID, action, thread_id);
+
+    vec![Component::ActionRow(ActionRow {
+        components: vec![
+            interactions::button(
+                custom_id("still_happening"),
+                "Still happening",
+                ButtonStyle::Danger,
+            ),
+            interactions::button(custom_id("fixed"), "Fixed for me", ButtonStyle::Success),
+            interactions::button(
+                custom_id("more_info"),
+                "Add more info",
+                ButtonStyle::Primary,
+            ),
+            interactions::button(custom_id("subscribe"), "Subscribe", ButtonStyle::Secondary),
+        ],
+    })]
+}
+
+/// Name of the user behind the interaction, as shown in Jira comments.
+fn user_name(interaction: &Interaction) -> String {
+    interaction
+        .author()
+        .map_or_else(|| "Someone".to_owned(), |user| user.name.clone())
+}
+
+/// Handles the feedback buttons on status cards and reporter notifications.
+pub async fn handle_button(ctx: &Context, interaction: &Interaction, target: &str) -> Result<()> {
+    let (action, thread_id) = target
+        .split_once(':')
+        .ok_or_else(|| Error::Parse(format!("invalid feedback target: {}", target)))?;
+    let thread_id = utils::parse_thread_id(thread_id, "feedback")?;
+
+    let link = match ctx.links.get(thread_id) {
+        Some(link) => link,
+        None => {
+            return respond_ephemeral(
+                ctx,
+                interaction,
+                "This thread isn't linked to a Jira issue anymore.",
+            )
+            .await
+        }
+    };
+
+    // resolving or reopening the bug is up to whoever reported it, or staff
+    let is_reporter = link.reporter_id.is_some()
+        && interaction.author_id().map(|user_id| user_id.get()) == link.reporter_id;
+    if matches!(action, "still_happening" | "fixed")
+        && !is_reporter
+        && !interactions::is_staff(interaction)
+    {
+        return respond_ephemeral(
+            ctx,
+            interaction,
+            "Only the person who reported this bug can say whether it's fixed.",
+        )
+        .await;
+    }
+
+    match action {
+        "still_happening" => {
+            let comment = format!(
+                "{} reported on Discord that this is still happening.",
+                user_name(interaction)
+            );
+            utils::add_jira_comment(ctx, &link.issue_key, &comment).await?;
+
+            // a resolved issue that is still happening gets reopened, if a transition for that is configured
+            if let Some(transition) = constants::STILL_HAPPENING_TRANSITION.as_deref() {
+                let issue = utils::get_jira_issue(ctx, &link.issue_key).await?;
+
+                if issue.fields.resolution.is_some() {
+                    utils::transition_jira_issue(ctx, &link.issue_key, transition).await?;
+                }
+            }
+
+            respond_ephemeral(ctx, interaction, "Thanks, we've let the team know.").await
+        }
+        "fixed" => {
+            let comment = format!(
+                "{} reported on Discord that this is fixed for them.",
+                user_name(interaction)
+            );
+            utils::add_jira_comment(ctx, &link.issue_key, &comment).await?;
+
+            if let Some(transition) = constants::FIXED_TRANSITION.as_deref() {
+                utils::transition_jira_issue(ctx, &link.issue_key, transition).await?;
+            }
+
+            respond_ephemeral(ctx, interaction, "Thanks for confirming!").await
+        }
+        "more_info" => {
+            respond_modal(
+                ctx,
+                interaction,
+                format!("{}:{}", MORE_INFO_MODAL_ID, thread_id),
+                "Add more info",
+                vec![text_input(
+                    "info",
+                    "What else should we know?",
+                    TextInputStyle::Paragraph,
+                    2000,
+                    true,
+                    None,
+                )],
+            )
+            .await
+        }
+        "subscribe" => {
+            let user_id = interaction
+                .author_id()
+                .ok_or_else(|| Error::Parse("subscribe clicked without a user".to_owned()))?;
+
+            let content = if subscriptions::subscribe(ctx, user_id.get(), &link.issue_key)? {
+                format!("You'll get a DM whenever {} changes.", link.issue_key)
+            } else {
+                format!("You're already subscribed to {}.", link.issue_key)
+            };
+
+            respond_ephemeral(ctx, interaction, &content).await
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Adds the info from the "Add more info" modal to the issue as a comment.
+pub async fn handle_more_info_modal(
+    ctx: &Context,
+    interaction: &Interaction,
+    data: &ModalInteractionData,
+    target: &str,
+) -> Result<()> {
+    let thread_id = utils::parse_thread_id(target, "feedback")?;
+
+    let link = match ctx.links.get(thread_id) {
+        Some(link) => link,
+        None => {
+            return respond_ephemeral(
+                ctx,
+                interaction,
+                "This thread isn't linked to a Jira issue anymore.",
+            )
+            .await
+        }
+    };
+
+    let info = interactions::modal_value(data, "info").unwrap_or_default();
+    let comment = format!(
+        "More info from {} on Discord:\n\n{}",
+        user_name(interaction),
+        info
+    );
+    utils::add_jira_comment(ctx, &link.issue_key, &comment).await?;
+
+    respond_ephemeral(ctx, interaction, "Thanks, it's been added to the issue.").await
+}
@@ -0,0 +1,399 @@
+use std::{
+    sync::{Arc, RwLock},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use serde::Serialize;
+use tokio::sync::watch;
+use tracing::Instrument;
+use twilight_gateway::{
+    error::ReceiveMessageErrorType, CloseFrame, ConfigBuilder, Event, EventTypeFlags, Intents,
+    Shard, ShardId,
+};
+use twilight_model::gateway::{payload::incoming::ThreadCreate, CloseCode};
+
+use crate::{
+    commands, constants, context::Context, deletions, edits, interactions, metrics, similar,
+    template, titles, unfurl, utils,
+};
+
+/// delay before the first restart of a shard, doubled after every consecutive failure
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+/// upper bound for the delay between shard restarts
+const MAX_BACKOFF: Duration = Duration::from_secs(300);
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GatewayState {
+    Connecting,
+    Connected,
+    /// the connection dropped and the shard is reconnecting on its own
+    Disconnected,
+    Restarting,
+    Stopped,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct GatewayStatus {
+    pub state: GatewayState,
+    /// number of times the shard has been restarted since the process started
+    pub restarts: u32,
+    pub last_error: Option<String>,
+    /// unix timestamp (in seconds) of the last state change
+    pub since: u64,
+    /// unix timestamp (in seconds) of the last event received from the gateway
+    pub last_event_at: Option<u64>,
+}
+
+impl Default for GatewayStatus {
+    fn default() -> Self {
+        GatewayStatus {
+            state: GatewayState::Connecting,
+            restarts: 0,
+            last_error: None,
+            since: unix_now(),
+            last_event_at: None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum GatewayError {
+    /// Discord rejected the bot token, restarting the shard won't help
+    InvalidToken,
+    /// Discord closed the connection with a code reconnecting can't fix, e.g. disallowed intents
+    FatallyClosed(CloseCode),
+}
+
+impl std::fmt::Display for GatewayError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GatewayError::InvalidToken => f.write_str("discord rejected the bot token"),
+            GatewayError::FatallyClosed(close_code) => {
+                write!(f, "gateway fatally closed: {}", close_code)
+            }
+        }
+    }
+}
+
+impl std::error::Error for GatewayError {}
+
+/// Why a shard stopped receiving events.
+enum ShardExit {
+    Fatal(CloseCode),
+    /// the shard was closed because the process is shutting down
+    Shutdown,
+}
+
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+pub fn unix_now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+fn set_state(status: &RwLock<GatewayStatus>, state: GatewayState, error: Option<String>) {
+    let mut status = status.write().unwrap();
+
+    if matches!(state, GatewayState::Restarting) {
+        status.restarts += 1;
+    }
+
+    if error.is_some() {
+        status.last_error = error;
+    }
+
+    metrics::GATEWAY_CONNECTED.set(matches!(state, GatewayState::Connected) as i64);
+
+    status.state = state;
+    status.since = unix_now();
+}
+
+/// Marks a connected shard as disconnected until it's ready or resumed again.
+fn set_disconnected(status: &RwLock<GatewayStatus>, reason: String) {
+    if matches!(status.read().unwrap().state, GatewayState::Connected) {
+        set_state(status, GatewayState::Disconnected, Some(reason));
+    }
+}
+
+/// Runs the shard and restarts it with exponential backoff whenever it dies.
+///
+/// Returns once the shard has been closed for shutdown, or with an error when the
+/// shard can never connect again, e.g. the token is invalid.
+pub async fn supervise(
+    ctx: Arc<Context>,
+    mut shutdown: watch::Receiver<bool>,
+) -> Result<(), GatewayError> {
+    let status = &ctx.gateway_status;
+    let mut backoff = INITIAL_BACKOFF;
+
+    loop {
+        set_state(status, GatewayState::Connecting, None);
+
+        // run the shard in its own task so a panic in an event handler is caught here too
+        let shard_task = tokio::spawn(run_shard(Arc::clone(&ctx), shutdown.clone()));
+
+        let error = match shard_task.await {
+            Ok(ShardExit::Shutdown) => {
+                set_state(status, GatewayState::Stopped, None);
+                return Ok(());
+            }
+            Ok(ShardExit::Fatal(CloseCode::AuthenticationFailed)) => {
+                tracing::error!("gateway closed: invalid discord token, shutting down");
+                set_state(
+                    status,
+                    GatewayState::Stopped,
+                    Some(GatewayError::InvalidToken.to_string()),
+                );
+
+                return Err(GatewayError::InvalidToken);
+            }
+            // twilight only closes fatally for codes it can't reconnect after
+            Ok(ShardExit::Fatal(close_code)) => {
+                let error = GatewayError::FatallyClosed(close_code);
+                tracing::error!("{}, shutting down", error);
+                set_state(status, GatewayState::Stopped, Some(error.to_string()));
+
+                return Err(error);
+            }
+            Err(join_error) => format!("shard task panicked: {}", join_error),
+        };
+
+        // a shard that managed to connect resets the backoff
+        if matches!(
+            status.read().unwrap().state,
+            GatewayState::Connected | GatewayState::Disconnected
+        ) {
+            backoff = INITIAL_BACKOFF;
+        }
+
+        tracing::warn!(?backoff, "{}, restarting", error);
+        set_state(status, GatewayState::Restarting, Some(error));
+
+        tokio::select! {
+            _ = tokio::time::sleep(backoff) => {}
+            _ = shutdown.changed() => {
+                set_state(status, GatewayState::Stopped, None);
+                return Ok(());
+            }
+        }
+
+        backoff = (backoff * 2).min(MAX_BACKOFF);
+    }
+}
+
+async fn run_shard(ctx: Arc<Context>, mut shutdown: watch::Receiver<bool>) -> ShardExit {
+    let token = constants::DISCORD_TOKEN.to_string();
+    let intents = Intents::GUILD_MESSAGES | Intents::GUILDS | Intents::MESSAGE_CONTENT;
+    let event_types = EventTypeFlags::GUILD_CREATE
+        | EventTypeFlags::THREAD_CREATE
+        | EventTypeFlags::THREAD_UPDATE
+        | EventTypeFlags::THREAD_DELETE
+        | EventTypeFlags::INTERACTION_CREATE
+        | EventTypeFlags::MESSAGE_CREATE
+        | EventTypeFlags::MESSAGE_UPDATE
+        | EventTypeFlags::MESSAGE_DELETE
+        | EventTypeFlags::READY
+        | EventTypeFlags::RESUMED;
+
+    let config = ConfigBuilder::new(token, intents)
+        .event_types(event_types)
+        .build();
+
+    let mut shard = Shard::with_config(ShardId::ONE, config);
+
+    loop {
+        let next_event = tokio::select! {
+            next_event = shard.next_event() => next_event,
+            _ = shutdown.changed() => {
+                if let Err(error) = shard.close(CloseFrame::NORMAL).await {
+                    tracing::warn!(?error, "error closing shard");
+                }
+
+                return ShardExit::Shutdown;
+            }
+        };
+
+        match next_event {
+            Ok(event) => {
+                ctx.gateway_status.write().unwrap().last_event_at = Some(unix_now());
+
+                let span = tracing::info_span!(
+                    "gateway_event",
+                    kind = ?event.kind(),
+                    thread_id = tracing::field::Empty,
+                );
+
+                if let Event::ThreadCreate(thread) = &event {
+                    span.record("thread_id", thread.id.get());
+                } else if let Event::ThreadUpdate(thread) = &event {
+                    span.record("thread_id", thread.id.get());
+                } else if let Event::ThreadDelete(thread) = &event {
+                    span.record("thread_id", thread.id.get());
+                }
+
+                handle_event(&ctx, event).instrument(span).await
+            }
+            Err(source) => {
+                tracing::warn!(?source, "error receiving event");
+                metrics::record_error("gateway");
+                set_disconnected(&ctx.gateway_status, source.to_string());
+
+                if let ReceiveMessageErrorType::FatallyClosed { close_code } = source.kind() {
+                    return ShardExit::Fatal(*close_code);
+                }
+            }
+        }
+    }
+}
+
+async fn handle_event(ctx: &Arc<Context>, event: Event) {
+    match event {
+        Event::Ready(ready) => {
+            set_state(&ctx.gateway_status, GatewayState::Connected, None);
+            ctx.bot_user_id.get_or_init(|| ready.user.id.get());
+
+            if let Err(error) = commands::register_commands(ctx, ready.application.id).await {
+                tracing::error!(%error, "error registering commands");
+                metrics::record_error(error.kind());
+            }
+        }
+        Event::Resumed => {
+            set_state(&ctx.gateway_status, GatewayState::Connected, None);
+        }
+        Event::GatewayClose(frame) => {
+            // the shard reconnects by itself, until then the bot isn't ready
+            let reason = match frame {
+                Some(frame) => format!("gateway closed with code {}", frame.code),
+                None => "gateway connection lost".to_owned(),
+            };
+            set_disconnected(&ctx.gateway_status, reason);
+        }
+        Event::InteractionCreate(interaction) => {
+            // interactions are answered on their own task so a slow Jira call doesn't hold up the shard
+            tokio::spawn(
+                interactions::handle_interaction(Arc::clone(ctx), interaction.0)
+                    .instrument(tracing::Span::current()),
+            );
+        }
+        Event::MessageCreate(message) => {
+            // unfurled on its own task, looking the issues up in Jira shouldn't hold up the shard
+            let task_ctx = Arc::clone(ctx);
+            tokio::spawn(
+                async move {
+                    if let Err(error) = unfurl::unfurl_issue_keys(&task_ctx, &message).await {
+                        tracing::error!(%error, "error unfurling issue keys");
+                        metrics::record_error(error.kind());
+                    }
+                }
+                .instrument(tracing::Span::current()),
+            );
+        }
+        Event::MessageUpdate(update) => {
+            let task_ctx = Arc::clone(ctx);
+            tokio::spawn(
+                async move {
+                    if let Err(error) = edits::starter_message_edited(&task_ctx, &update).await {
+                        tracing::error!(%error, "error syncing starter message edit");
+                        metrics::record_error(error.kind());
+                    }
+                }
+                .instrument(tracing::Span::current()),
+            );
+        }
+        Event::MessageDelete(message) => {
+            let task_ctx = Arc::clone(ctx);
+            tokio::spawn(
+                async move {
+                    if let Err(error) =
+                        deletions::starter_message_deleted(&task_ctx, &message).await
+                    {
+                        tracing::error!(%error, "error handling deleted starter message");
+                        metrics::record_error(error.kind());
+                    }
+                }
+                .instrument(tracing::Span::current()),
+            );
+        }
+        Event::ThreadCreate(ref thread) => {
+            // checked on its own task, fetching the starter message shouldn't hold up the shard
+            let task_ctx = Arc::clone(ctx);
+            let thread = thread.0.clone();
+            tokio::spawn(
+                async move {
+                    if let Err(error) = template::check_new_post(&task_ctx, &thread).await {
+                        tracing::error!(%error, "error checking bug report template");
+                        metrics::record_error(error.kind());
+                    }
+
+                    if let Err(error) = similar::suggest_similar_issues(&task_ctx, &thread).await {
+                        tracing::error!(%error, "error suggesting similar issues");
+                        metrics::record_error(error.kind());
+                    }
+                }
+                .instrument(tracing::Span::current()),
+            );
+
+            if let Err(error) = utils::handle_tag_updates(ctx, &event).await {
+                tracing::error!(%error, "error handling tag updates");
+                metrics::record_error(error.kind());
+            }
+
+            ctx.cache.update(&event);
+        }
+        Event::ThreadUpdate(ref thread) => {
+            // renames are synced on their own task, like the template check
+            let task_ctx = Arc::clone(ctx);
+            let thread = thread.0.clone();
+            tokio::spawn(
+                async move {
+                    if let Err(error) = titles::thread_renamed(&task_ctx, &thread).await {
+                        tracing::error!(%error, "error syncing thread name");
+                        metrics::record_error(error.kind());
+                    }
+                }
+                .instrument(tracing::Span::current()),
+            );
+
+            if let Err(error) = utils::handle_tag_updates(ctx, &event).await {
+                tracing::error!(%error, "error handling tag updates");
+                metrics::record_error(error.kind());
+            }
+
+            ctx.cache.update(&event);
+        }
+        Event::ThreadDelete(ref thread) => {
+            let task_ctx = Arc::clone(ctx);
+            let thread = thread.clone();
+            tokio::spawn(
+                async move {
+                    if let Err(error) = deletions::thread_deleted(&task_ctx, &thread).await {
+                        tracing::error!(%error, "error handling deleted thread");
+                        metrics::record_error(error.kind());
+                    }
+                }
+                .instrument(tracing::Span::current()),
+            );
+
+            ctx.cache.update(&event);
+        }
+        Event::GuildCreate(ref guild) => {
+            if guild.id != *constants::GUILD_ID {
+                return;
+            }
+            // cache all threads
+            for thread in &guild.threads {
+                ctx.cache
+                    .update(&Event::ThreadCreate(Box::new(ThreadCreate(thread.clone()))));
+            }
+        }
+        _ => {}
+    }
+}
@@ -1,29 +1,225 @@
-use actix_web::{get, post, web::Bytes, HttpRequest, HttpResponse};
+use actix_web::{
+    get, post,
+    web::{self, Bytes},
+    HttpRequest, HttpResponse, HttpResponseBuilder,
+};
+
+use prometheus::{Encoder, TextEncoder};
+
+use crate::{
+    constants,
+    context::Context,
+    duplicates,
+    gateway::{unix_now, GatewayState},
+    metrics,
+    models::{
+        CheckReport, JiraChangelogItem, JiraIssueDetails, JiraWebhook, LivenessReport,
+        ReadinessReport,
+    },
+    moves, status, storage,
+    subscriptions::{self, SubscriptionEvent},
+    titles, utils,
+};
+
+/// changelog fields shown on the status card
+const CARD_FIELDS: [&str; 6] = [
+    "summary",
+    "status",
+    "assignee",
+    "priority",
+    "Fix Version",
+    "resolution",
+];
 
 #[post("")]
 pub async fn jira_issue_update(
+    ctx: web::Data<Context>,
     _request: HttpRequest,
     bytes: Bytes,
 ) -> Result<HttpResponse, actix_web::Error> {
     // turn actix bytes to string then print
-    let body = String::from_utf8(bytes.to_vec()).unwrap();
-    println!("body: {:?}", body);
+    let body = match String::from_utf8(bytes.to_vec()) {
+        Ok(body) => body,
+        Err(error) => {
+            tracing::warn!(%error, "jira webhook body isn't valid utf-8");
+            metrics::WEBHOOKS_RECEIVED
+                .with_label_values(&["unknown"])
+                .inc();
+            return Ok(HttpResponse::BadRequest().body("invalid utf-8"));
+        }
+    };
+    tracing::debug!(%body, "received jira webhook");
+
+    let webhook = serde_json::from_str::<JiraWebhook>(&body);
+    let event = webhook
+        .as_ref()
+        .map(|webhook| webhook.webhook_event.as_str())
+        .unwrap_or("unknown");
+    metrics::WEBHOOKS_RECEIVED.with_label_values(&[event]).inc();
+
+    let webhook = match webhook {
+        Ok(webhook) => webhook,
+        Err(error) => {
+            tracing::warn!(%error, "couldn't parse jira webhook");
+            return Ok(HttpResponse::Ok().body("OK"));
+        }
+    };
+
+    // errors are only logged, jira would just keep retrying the same webhook
+    if let Err(error) = handle_webhook(&ctx, webhook).await {
+        tracing::error!(%error, "error handling jira webhook");
+        metrics::record_error(error.kind());
+    }
+
+    Ok(HttpResponse::Ok().body("OK"))
+}
+
+async fn handle_webhook(ctx: &Context, webhook: JiraWebhook) -> crate::error::Result<()> {
+    // issue link events are the only ones without an issue
+    if webhook.webhook_event == "issuelink_created" {
+        return match webhook.issue_link {
+            Some(link) => duplicates::issue_link_created(ctx, &link).await,
+            None => Ok(()),
+        };
+    }
+
+    let issue = match webhook.issue {
+        Some(issue) => issue,
+        None => return Ok(()),
+    };
+
+    match webhook.webhook_event.as_str() {
+        "jira:issue_updated" => {
+            let items = webhook
+                .changelog
+                .map(|changelog| changelog.items)
+                .unwrap_or_default();
+            let changed_by = webhook.user.as_ref().map(|user| user.display_name.as_str());
+
+            issue_updated(ctx, &issue, &items, changed_by).await
+        }
+        "jira:issue_deleted" => moves::issue_deleted(ctx, &issue).await,
+        "comment_created" => match webhook.comment {
+            Some(comment) => subscriptions::notify_comment(ctx, &issue, &comment).await,
+            None => Ok(()),
+        },
+        _ => Ok(()),
+    }
+}
+
+async fn issue_updated(
+    ctx: &Context,
+    issue: &JiraIssueDetails,
+    items: &[JiraChangelogItem],
+    changed_by: Option<&str>,
+) -> crate::error::Result<()> {
+    // moving an issue to another project gives it a new key, the old one is in the changelog
+    if let Some(old_key) = items
+        .iter()
+        .find(|item| item.field == "Key")
+        .and_then(|item| item.from.as_deref())
+    {
+        return moves::issue_moved(ctx, old_key, issue, changed_by).await;
+    }
+
+    // the card only shows some fields, no need to touch it for every description tweak
+    if !items.is_empty()
+        && !items
+            .iter()
+            .any(|item| CARD_FIELDS.contains(&item.field.as_str()))
+    {
+        return Ok(());
+    }
+
+    // personal subscriptions are by issue, they don't need the issue to have a thread
+    let events = SubscriptionEvent::from_changelog(items);
+    if !events.is_empty() {
+        subscriptions::notify_issue_update(ctx, issue, &events, changed_by).await?;
+    }
 
     // using the Jira API, we're listening for webhook requests about issue updates
-    // parse the description of the issue update to get the message URL
-    // send a message to the channel where the bug report originated with the issue update
-    // return a 200 OK response
+    // find the threads the bug was reported in and update their status cards
+    let notable_transition = status::notable_transition(items);
+    for thread_id in utils::threads_for_issue(ctx, issue).await? {
+        if items.iter().any(|item| item.field == "summary") {
+            titles::summary_changed(ctx, thread_id, &issue.fields.summary).await?;
+        }
 
-    // let parsed_description = crate::utils::parse_message_url_from_issue_update(&data.issue.fields.description);
+        status::refresh_status_card(ctx, thread_id, issue, changed_by).await?;
 
-    // let message = format!("Your bug report has been updated. The new status is {}.", data.issue.fields.status.name);
+        // notable transitions also get their own message so the reporter is pinged
+        if let Some(message) = &notable_transition {
+            status::notify_reporter(ctx, thread_id, message).await?;
+        }
+    }
 
-    // crate::utils::send_update_to_user_report(parsed_description.channel_id.get(), &message).await.unwrap();
+    if duplicates::resolved_as_duplicate(items) {
+        duplicates::issue_resolved_as_duplicate(ctx, issue).await?;
+    }
 
-    Ok(HttpResponse::Ok().body("OK"))
+    Ok(())
 }
 
-#[get("")]
-pub async fn testing() -> Result<HttpResponse, actix_web::Error> {
-    Ok(HttpResponse::Ok().body("OK"))
+/// Liveness: the process is up and the gateway supervisor hasn't given up.
+#[get("/healthz")]
+pub async fn healthz(ctx: web::Data<Context>) -> Result<HttpResponse, actix_web::Error> {
+    let gateway = ctx.gateway_status.read().unwrap().clone();
+    let healthy = !matches!(gateway.state, GatewayState::Stopped);
+
+    let report = LivenessReport {
+        healthy,
+        seconds_since_last_event: gateway
+            .last_event_at
+            .map(|last_event_at| unix_now().saturating_sub(last_event_at)),
+        gateway,
+    };
+
+    Ok(health_response(healthy).json(report))
+}
+
+/// Readiness: the gateway is connected, and Jira, storage and the sync queue are healthy.
+#[get("/readyz")]
+pub async fn readyz(ctx: web::Data<Context>) -> Result<HttpResponse, actix_web::Error> {
+    let gateway = ctx.gateway_status.read().unwrap().clone();
+    let jira = ctx.jira.check_reachability().await;
+    let storage = storage::check_health();
+    let queue_depth = ctx.jobs.len();
+
+    let ready = matches!(gateway.state, GatewayState::Connected)
+        && jira.is_ok()
+        && storage.is_ok()
+        && queue_depth <= *constants::READY_MAX_QUEUE_DEPTH;
+
+    let report = ReadinessReport {
+        ready,
+        seconds_since_last_event: gateway
+            .last_event_at
+            .map(|last_event_at| unix_now().saturating_sub(last_event_at)),
+        gateway,
+        jira: CheckReport::from(jira),
+        storage: CheckReport::from(storage),
+        queue_depth,
+    };
+
+    Ok(health_response(ready).json(report))
+}
+
+fn health_response(healthy: bool) -> HttpResponseBuilder {
+    if healthy {
+        HttpResponse::Ok()
+    } else {
+        HttpResponse::ServiceUnavailable()
+    }
+}
+
+#[get("/metrics")]
+pub async fn export_metrics() -> Result<HttpResponse, actix_web::Error> {
+    let mut buffer = Vec::new();
+    TextEncoder::new()
+        .encode(&prometheus::gather(), &mut buffer)
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+
+    Ok(HttpResponse::Ok()
+        .content_type(TextEncoder::new().format_type())
+        .body(buffer))
 }
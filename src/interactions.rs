@@ -0,0 +1,292 @@
+use std::sync::Arc;
+
+use twilight_model::{
+    application::interaction::{modal::ModalInteractionData, Interaction, InteractionData},
+    channel::message::{
+        component::{ActionRow, Button, ButtonStyle, TextInput, TextInputStyle},
+        Component, MessageFlags,
+    },
+    guild::Permissions,
+    http::interaction::{InteractionResponse, InteractionResponseData, InteractionResponseType},
+    id::Id,
+};
+
+use crate::{
+    affected, commands, constants,
+    context::Context,
+    error::Result,
+    feedback,
+    metrics::{self, DISCORD_REQUEST_DURATION},
+    review, similar, subscriptions,
+};
+
+/// Routes an interaction from the gateway to the command or component that handles it.
+pub async fn handle_interaction(ctx: Arc<Context>, interaction: Interaction) {
+    let result = match &interaction.data {
+        Some(InteractionData::ApplicationCommand(data)) => match data.name.as_str() {
+            commands::JIRA_COMMAND_NAME => {
+                commands::handle_jira_command(&ctx, &interaction, data).await
+            }
+            commands::SEND_TO_JIRA_COMMAND_NAME => {
+                commands::handle_send_to_jira_command(&ctx, &interaction, data).await
+            }
+            commands::REPORT_BUG_COMMAND_NAME => {
+                commands::handle_report_bug_command(&ctx, &interaction).await
+            }
+            _ => Ok(()),
+        },
+        Some(InteractionData::MessageComponent(data)) => {
+            // custom ids are "<component>:<target>"
+            let (component, target) = data
+                .custom_id
+                .split_once(':')
+                .unwrap_or((data.custom_id.as_str(), ""));
+
+            match component {
+                review::REVIEW_BUTTON_ID => review::handle_button(&ctx, &interaction, target).await,
+                feedback::FEEDBACK_BUTTON_ID => {
+                    feedback::handle_button(&ctx, &interaction, target).await
+                }
+                affected::AFFECTED_BUTTON_ID => {
+                    affected::handle_button(&ctx, &interaction, target).await
+                }
+                similar::SIMILAR_BUTTON_ID => {
+                    similar::handle_button(&ctx, &interaction, target).await
+                }
+                subscriptions::SUBSCRIPTION_BUTTON_ID => {
+                    subscriptions::handle_button(&ctx, &interaction, target).await
+                }
+                _ => Ok(()),
+            }
+        }
+        Some(InteractionData::ModalSubmit(data)) => {
+            // custom ids are "<modal>" or "<modal>:<target>"
+            let (modal, target) = data
+                .custom_id
+                .split_once(':')
+                .unwrap_or((data.custom_id.as_str(), ""));
+
+            match modal {
+                commands::SEND_TO_JIRA_MODAL_ID => {
+                    commands::handle_send_to_jira_modal(&ctx, &interaction, data, target).await
+                }
+                commands::REPORT_BUG_MODAL_ID => {
+                    commands::handle_report_bug_modal(&ctx, &interaction, data).await
+                }
+                review::REVIEW_EDIT_MODAL_ID => {
+                    review::handle_edit_modal(&ctx, &interaction, data, target).await
+                }
+                feedback::MORE_INFO_MODAL_ID => {
+                    feedback::handle_more_info_modal(&ctx, &interaction, data, target).await
+                }
+                _ => Ok(()),
+            }
+        }
+        _ => Ok(()),
+    };
+
+    let deferred = ctx
+        .deferred_interactions
+        .lock()
+        .unwrap()
+        .remove(&interaction.id.get());
+
+    if let Err(error) = result {
+        tracing::error!(%error, "error handling interaction");
+        metrics::record_error(error.kind());
+
+        // let the user know instead of leaving the interaction hanging, a deferred
+        // interaction has to be answered by editing its "thinking" response
+        let content = "Something went wrong, please try again later.";
+        let response = if deferred {
+            update_deferred(&ctx, &interaction, content).await
+        } else {
+            respond_ephemeral(&ctx, &interaction, content).await
+        };
+
+        if let Err(error) = response {
+            tracing::warn!(%error, "couldn't tell the user the interaction failed");
+        }
+    }
+}
+
+/// Whether the user behind the interaction is staff, i.e. has one of the configured
+/// staff roles or is an administrator.
+pub fn is_staff(interaction: &Interaction) -> bool {
+    let member = match &interaction.member {
+        Some(member) => member,
+        None => return false,
+    };
+
+    let is_admin = member
+        .permissions
+        .is_some_and(|permissions| permissions.contains(Permissions::ADMINISTRATOR));
+
+    is_admin
+        || member
+            .roles
+            .iter()
+            .any(|role| constants::STAFF_ROLE_IDS.contains(&role.get()))
+}
+
+pub async fn respond_ephemeral(
+    ctx: &Context,
+    interaction: &Interaction,
+    content: &str,
+) -> Result<()> {
+    let response = InteractionResponse {
+        kind: InteractionResponseType::ChannelMessageWithSource,
+        data: Some(InteractionResponseData {
+            content: Some(content.to_owned()),
+            flags: Some(MessageFlags::EPHEMERAL),
+            ..Default::default()
+        }),
+    };
+
+    let interaction_client = ctx.discord.interaction(interaction.application_id);
+    let request = interaction_client.create_response(interaction.id, &interaction.token, &response);
+    metrics::observe(&DISCORD_REQUEST_DURATION, "create_response", request).await?;
+
+    Ok(())
+}
+
+/// Acknowledges the interaction so there is more than three seconds to respond,
+/// showing the user a private "thinking" state until [`update_deferred`] is called.
+pub async fn defer_ephemeral(ctx: &Context, interaction: &Interaction) -> Result<()> {
+    let response = InteractionResponse {
+        kind: InteractionResponseType::DeferredChannelMessageWithSource,
+        data: Some(InteractionResponseData {
+            flags: Some(MessageFlags::EPHEMERAL),
+            ..Default::default()
+        }),
+    };
+
+    let interaction_client = ctx.discord.interaction(interaction.application_id);
+    let request = interaction_client.create_response(interaction.id, &interaction.token, &response);
+    metrics::observe(&DISCORD_REQUEST_DURATION, "create_response", request).await?;
+
+    ctx.deferred_interactions
+        .lock()
+        .unwrap()
+        .insert(interaction.id.get());
+
+    Ok(())
+}
+
+/// Opens a modal, the submission comes back as an interaction with the same `custom_id`.
+pub async fn respond_modal(
+    ctx: &Context,
+    interaction: &Interaction,
+    custom_id: String,
+    title: &str,
+    inputs: Vec<TextInput>,
+) -> Result<()> {
+    // every text input has to sit in its own action row
+    let components = inputs
+        .into_iter()
+        .map(|input| {
+            Component::ActionRow(ActionRow {
+                components: vec![Component::TextInput(input)],
+            })
+        })
+        .collect();
+
+    let response = InteractionResponse {
+        kind: InteractionResponseType::Modal,
+        data: Some(InteractionResponseData {
+            custom_id: Some(custom_id),
+            title: Some(title.to_owned()),
+            components: Some(components),
+            ..Default::default()
+        }),
+    };
+
+    let interaction_client = ctx.discord.interaction(interaction.application_id);
+    let request = interaction_client.create_response(interaction.id, &interaction.token, &response);
+    metrics::observe(&DISCORD_REQUEST_DURATION, "create_response", request).await?;
+
+    Ok(())
+}
+
+/// Edits the message the component was clicked on, fields left as `None` are kept as they are.
+pub async fn update_message(
+    ctx: &Context,
+    interaction: &Interaction,
+    data: InteractionResponseData,
+) -> Result<()> {
+    let response = InteractionResponse {
+        kind: InteractionResponseType::UpdateMessage,
+        data: Some(data),
+    };
+
+    let interaction_client = ctx.discord.interaction(interaction.application_id);
+    let request = interaction_client.create_response(interaction.id, &interaction.token, &response);
+    metrics::observe(&DISCORD_REQUEST_DURATION, "create_response", request).await?;
+
+    Ok(())
+}
+
+pub fn button(custom_id: String, label: &str, style: ButtonStyle) -> Component {
+    Component::Button(Button {
+        custom_id: Some(custom_id),
+        disabled: false,
+        emoji: None,
+        label: Some(label.to_owned()),
+        style,
+        url: None,
+    })
+}
+
+pub fn text_input(
+    custom_id: &str,
+    label: &str,
+    style: TextInputStyle,
+    max_length: u16,
+    required: bool,
+    value: Option<String>,
+) -> TextInput {
+    TextInput {
+        custom_id: custom_id.to_owned(),
+        label: label.to_owned(),
+        max_length: Some(max_length),
+        min_length: None,
+        placeholder: None,
+        required: Some(required),
+        style,
+        value: value.filter(|value| !value.is_empty()),
+    }
+}
+
+/// Trimmed value of a text input in a submitted modal, `None` if it was left empty.
+pub fn modal_value<'a>(data: &'a ModalInteractionData, custom_id: &str) -> Option<&'a str> {
+    data.components
+        .iter()
+        .flat_map(|row| &row.components)
+        .find(|component| component.custom_id == custom_id)
+        .and_then(|component| component.value.as_deref())
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+}
+
+pub async fn update_deferred(
+    ctx: &Context,
+    interaction: &Interaction,
+    content: &str,
+) -> Result<()> {
+    let interaction_client = ctx.discord.interaction(interaction.application_id);
+    let request = interaction_client
+        .update_response(&interaction.token)
+        .content(Some(content))?;
+    metrics::observe(&DISCORD_REQUEST_DURATION, "update_response", request).await?;
+
+    Ok(())
+}
+
+/// Id of the bug report thread the interaction was used in, if it was used in one.
+pub fn bug_report_thread_id(interaction: &Interaction) -> Option<u64> {
+    let channel = interaction.channel.as_ref()?;
+
+    (channel.kind.is_thread()
+        && channel.parent_id == Some(Id::new(*constants::BUG_REPORT_CHANNEL_ID)))
+    .then(|| channel.id.get())
+}
@@ -0,0 +1,72 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use reqwest::{Method, RequestBuilder, Response};
+
+use crate::{
+    constants,
+    error::{Error, Result},
+    metrics::{self, JIRA_REQUEST_DURATION},
+};
+
+/// how long the result of a reachability check is reused before Jira is asked again
+const REACHABILITY_TTL: Duration = Duration::from_secs(60);
+
+/// Thin wrapper around a shared [`reqwest::Client`] that knows where the Jira API lives
+/// and how to authenticate against it.
+pub struct JiraClient {
+    http: reqwest::Client,
+    base_url: String,
+    username: String,
+    token: String,
+    reachability: Mutex<Option<(Instant, std::result::Result<(), String>)>>,
+}
+
+impl JiraClient {
+    pub fn new(http: reqwest::Client) -> Self {
+        JiraClient {
+            http,
+            base_url: constants::JIRA_BASE_URL.to_string(),
+            username: constants::JIRA_USERNAME.to_string(),
+            token: constants::JIRA_TOKEN.to_string(),
+            reachability: Mutex::new(None),
+        }
+    }
+
+    /// Builds an authenticated request against `/rest/api/2/{path}`.
+    pub fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        self.http
+            .request(method, format!("{}/rest/api/2/{}", self.base_url, path))
+            .basic_auth(&self.username, Some(&self.token))
+    }
+
+    /// Sends the request, recording its latency under `operation` and turning error
+    /// responses into [`Error::JiraHttp`].
+    pub async fn send(&self, operation: &str, request: RequestBuilder) -> Result<Response> {
+        let response = metrics::observe(&JIRA_REQUEST_DURATION, operation, request.send()).await?;
+
+        Error::from_jira_response(response).await
+    }
+
+    /// Checks that Jira is reachable and accepts our credentials by fetching the
+    /// authenticated user, reusing the last result for [`REACHABILITY_TTL`].
+    pub async fn check_reachability(&self) -> std::result::Result<(), String> {
+        if let Some((checked_at, result)) = &*self.reachability.lock().unwrap() {
+            if checked_at.elapsed() < REACHABILITY_TTL {
+                return result.clone();
+            }
+        }
+
+        let result = self
+            .send("myself", self.request(Method::GET, "myself"))
+            .await
+            .map(|_| ())
+            .map_err(|error| error.to_string());
+
+        *self.reachability.lock().unwrap() = Some((Instant::now(), result.clone()));
+
+        result
+    }
+}
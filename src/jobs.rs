@@ -0,0 +1,188 @@
+use std::{
+    collections::HashMap,
+    path::Path,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::Notify;
+use tracing::Instrument;
+use uuid::Uuid;
+
+use crate::{constants, context::Context, metrics, storage, utils};
+
+/// how many times a sync is attempted when it keeps failing with retryable errors
+const MAX_SYNC_ATTEMPTS: u32 = 3;
+/// delay before retrying a failed sync, multiplied by the number of attempts so far
+const RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// Progress of a single thread being synced to Jira.
+///
+/// Every step is recorded as soon as it completes so a job that gets interrupted
+/// can be resumed without creating a second issue or re-uploading attachments.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct SyncJob {
+    pub thread_id: u64,
+    /// identifies this sync in logs and in the Jira comment left on the issue
+    pub correlation_id: String,
+    pub issue_key: Option<String>,
+    /// name of the Jira issue type to create, the default issue type is used when unset
+    pub issue_type: Option<String>,
+    /// summary and description set during review, replacing the ones taken from the thread
+    pub summary: Option<String>,
+    pub description: Option<String>,
+    /// staff member who approved the issue, when review mode is enabled
+    pub approved_by: Option<String>,
+    pub uploaded_attachments: Vec<u64>,
+    pub commented: bool,
+    pub replied: bool,
+}
+
+impl SyncJob {
+    pub fn new(thread_id: u64) -> Self {
+        SyncJob {
+            thread_id,
+            correlation_id: Uuid::new_v4().to_string(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Tracks the sync jobs that are currently running so they can be drained on shutdown.
+#[derive(Default)]
+pub struct SyncJobs {
+    jobs: Mutex<HashMap<u64, SyncJob>>,
+    finished: Notify,
+}
+
+impl SyncJobs {
+    /// Spawns a sync job for the thread unless one is already running for it.
+    pub fn spawn(&self, ctx: &Arc<Context>, job: SyncJob) {
+        let thread_id = job.thread_id;
+        let job_correlation_id = job.correlation_id.clone();
+
+        {
+            let mut jobs = self.jobs.lock().unwrap();
+            if jobs.contains_key(&thread_id) {
+                return;
+            }
+            jobs.insert(thread_id, job);
+            metrics::SYNC_QUEUE_DEPTH.set(jobs.len() as i64);
+        }
+
+        let span = tracing::info_span!(
+            "sync_job",
+            thread_id,
+            correlation_id = %job_correlation_id,
+            jira_key = tracing::field::Empty,
+        );
+
+        let ctx = Arc::clone(ctx);
+        tokio::spawn(
+            async move {
+                let mut attempt = 1;
+
+                while let Err(error) = utils::sync_thread_to_jira(&ctx, thread_id).await {
+                    metrics::record_error(error.kind());
+
+                    if !error.is_retryable() || attempt >= MAX_SYNC_ATTEMPTS {
+                        tracing::error!(%error, attempt, "error syncing thread");
+                        break;
+                    }
+
+                    let delay = RETRY_DELAY * attempt;
+                    tracing::warn!(%error, attempt, ?delay, "error syncing thread, retrying");
+
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+
+                let mut jobs = ctx.jobs.jobs.lock().unwrap();
+                jobs.remove(&thread_id);
+                metrics::SYNC_QUEUE_DEPTH.set(jobs.len() as i64);
+                drop(jobs);
+
+                ctx.jobs.finished.notify_waiters();
+            }
+            .instrument(span),
+        );
+    }
+
+    pub fn get(&self, thread_id: u64) -> Option<SyncJob> {
+        self.jobs.lock().unwrap().get(&thread_id).cloned()
+    }
+
+    /// Records progress made on a running job.
+    pub fn update(&self, thread_id: u64, update: impl FnOnce(&mut SyncJob)) {
+        if let Some(job) = self.jobs.lock().unwrap().get_mut(&thread_id) {
+            update(job);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.jobs.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Waits for the running jobs to finish, persisting whatever is left once the timeout elapses.
+    pub async fn drain(&self, timeout: Duration) {
+        let wait_for_jobs = async {
+            loop {
+                let finished = self.finished.notified();
+
+                if self.is_empty() {
+                    return;
+                }
+
+                finished.await;
+            }
+        };
+
+        if tokio::time::timeout(timeout, wait_for_jobs).await.is_ok() {
+            tracing::info!("all sync jobs finished");
+            return;
+        }
+
+        let unfinished: Vec<SyncJob> = self.jobs.lock().unwrap().values().cloned().collect();
+
+        tracing::warn!(
+            count = unfinished.len(),
+            "sync jobs didn't finish in time, persisting them"
+        );
+
+        let path = Path::new(constants::PENDING_SYNCS_PATH.as_str());
+
+        if let Err(error) = storage::write_json(path, &unfinished) {
+            tracing::error!(?error, "error persisting unfinished sync jobs");
+            metrics::record_error("storage");
+        }
+    }
+
+    /// Restarts the jobs that were persisted by the last shutdown.
+    pub fn resume_persisted(&self, ctx: &Arc<Context>) {
+        let path = Path::new(constants::PENDING_SYNCS_PATH.as_str());
+
+        let unfinished: Vec<SyncJob> = match storage::read_json(path) {
+            Ok(Some(unfinished)) => unfinished,
+            Ok(None) => return,
+            Err(error) => {
+                tracing::error!(%error, "error reading unfinished sync jobs");
+                return;
+            }
+        };
+
+        if let Err(error) = std::fs::remove_file(path) {
+            tracing::error!(?error, "error removing unfinished sync jobs");
+        }
+
+        tracing::info!(count = unfinished.len(), "resuming unfinished sync jobs");
+
+        for job in unfinished {
+            self.spawn(ctx, job);
+        }
+    }
+}
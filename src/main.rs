@@ -1,102 +1,129 @@
+mod affected;
+mod audit;
+mod commands;
 pub mod constants;
+mod context;
+mod deletions;
+mod duplicates;
+mod edits;
+mod embeds;
+mod error;
+mod feedback;
+mod gateway;
 mod handlers;
+mod interactions;
+mod jira;
+mod jobs;
+mod metrics;
 mod middleware;
 pub mod models;
+mod moves;
+mod report;
+mod review;
+mod similar;
+mod status;
+mod storage;
+mod subscriptions;
+mod template;
+mod titles;
+mod unfurl;
 pub mod utils;
 
 extern crate twilight_gateway;
 extern crate twilight_http;
 extern crate twilight_model;
 
-use std::error::Error;
+use std::{error::Error, sync::Arc, time::Duration};
 
 use actix_web::{web, App, HttpServer};
-use twilight_cache_inmemory::InMemoryCache;
-use twilight_gateway::{ConfigBuilder, Event, EventTypeFlags, Intents, Shard, ShardId};
-use twilight_model::gateway::payload::incoming::ThreadCreate;
+use tokio::{
+    signal::unix::{signal, SignalKind},
+    sync::watch,
+};
+
+use tracing_subscriber::EnvFilter;
+
+use crate::context::Context;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error + Send + Sync>> {
     dotenv::dotenv().ok();
+    init_tracing();
+
     let server_addr = dotenv::var("SERVER_ADDR").unwrap_or_else(|_| "127.0.0.1:8080".to_string());
 
+    let ctx = Arc::new(Context::new()?);
+    let ctx_data = web::Data::from(Arc::clone(&ctx));
+
     let server = HttpServer::new(move || {
-        App::new().service(
-            web::scope("/discord-jira")
-                .wrap(middleware::LoggingRoute {})
-                // .guard(guard::Header("content-type", "application/json"))
-                // .guard(guard::Post())
-                .service(handlers::jira_issue_update)
-                .service(handlers::testing),
-        )
+        App::new()
+            .app_data(ctx_data.clone())
+            .service(handlers::export_metrics)
+            .service(handlers::healthz)
+            .service(handlers::readyz)
+            .service(
+                web::scope("/discord-jira")
+                    .wrap(middleware::LoggingRoute {})
+                    // .guard(guard::Header("content-type", "application/json"))
+                    // .guard(guard::Post())
+                    .service(handlers::jira_issue_update),
+            )
     })
     .bind(server_addr.clone())?
+    // shutdown is coordinated below so in-flight syncs can be drained
+    .disable_signals()
     .run();
 
-    let _event_loop_task = tokio::spawn(async move {
-        // Initialize the tracing subscriber.
-        // tracing_subscriber::fmt::init();
-
-        let token = dotenv::var("DISCORD_TOKEN").unwrap();
-        let intents = Intents::GUILD_MESSAGES | Intents::GUILDS | Intents::MESSAGE_CONTENT;
-        let event_types = EventTypeFlags::THREAD_CREATE
-            | EventTypeFlags::THREAD_UPDATE
-            | EventTypeFlags::THREAD_DELETE;
-
-        let config = ConfigBuilder::new(token.clone(), intents)
-            .event_types(event_types)
-            .build();
-
-        let mut shard = Shard::with_config(ShardId::ONE, config);
-
-        let cache = InMemoryCache::builder()
-            .resource_types(twilight_cache_inmemory::ResourceType::CHANNEL)
-            .build();
-
-        loop {
-            match shard.next_event().await {
-                Ok(event) => {
-                    match event {
-                        Event::ThreadCreate(_)
-                        | Event::ThreadUpdate(_)
-                        | Event::ThreadDelete(_) => {
-                            let handled_data = utils::handle_tag_updates(&cache, &event).await;
-
-                            if handled_data.is_err() {
-                                println!("error handling tag updates: {:?}", handled_data);
-                            }
-
-                            cache.update(&event);
-                        }
-                        Event::GuildCreate(ref guild) => {
-                            if guild.id != *constants::GUILD_ID {
-                                continue;
-                            }
-                            // cache all threads
-                            for thread in &guild.threads {
-                                cache.update(&Event::ThreadCreate(Box::new(ThreadCreate(
-                                    thread.clone(),
-                                ))));
-                            }
-                        }
-                        _ => {}
-                    }
-                }
-                Err(source) => {
-                    println!("error receiving event: {:?}", source);
-                    if source.is_fatal() {
-                        break;
-                    }
-
-                    continue;
-                }
-            };
+    let server_handle = server.handle();
+
+    tracing::info!("Server running at http://{}/", server_addr);
+
+    ctx.jobs.resume_persisted(&ctx);
+
+    let (shutdown_sender, shutdown_receiver) = watch::channel(false);
+    let gateway = gateway::supervise(Arc::clone(&ctx), shutdown_receiver);
+
+    tokio::pin!(server, gateway);
+
+    tokio::select! {
+        result = &mut server => result?,
+        result = &mut gateway => {
+            // the supervisor only gives up when the gateway can never reconnect,
+            // so take the webhook server down with it and exit with an error
+            server_handle.stop(true).await;
+            result?;
         }
-    });
+        _ = shutdown_signal() => {
+            tracing::info!(jobs = ctx.jobs.len(), "shutting down, draining sync jobs");
 
-    println!("Server running at http://{}/", server_addr);
+            // stop accepting webhooks, then close the shard so no new syncs get started
+            server_handle.stop(true).await;
+            shutdown_sender.send(true).ok();
+            gateway.await?;
 
-    server.await?;
+            ctx.jobs.drain(Duration::from_secs(*constants::SHUTDOWN_TIMEOUT_SECS))
+                .await;
+        }
+    }
 
     Ok(())
 }
+
+fn init_tracing() {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    let subscriber = tracing_subscriber::fmt().with_env_filter(filter);
+
+    match constants::LOG_FORMAT.as_str() {
+        "json" => subscriber.json().init(),
+        _ => subscriber.pretty().init(),
+    }
+}
+
+async fn shutdown_signal() {
+    let mut terminate = signal(SignalKind::terminate()).expect("failed to listen for SIGTERM");
+
+    tokio::select! {
+        _ = tokio::signal::ctrl_c() => {}
+        _ = terminate.recv() => {}
+    }
+}
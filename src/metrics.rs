@@ -0,0 +1,71 @@
+use std::{future::IntoFuture, time::Instant};
+
+use lazy_static::lazy_static;
+use prometheus::{
+    register_histogram_vec, register_int_counter, register_int_counter_vec, register_int_gauge,
+    HistogramVec, IntCounter, IntCounterVec, IntGauge,
+};
+
+lazy_static! {
+    pub static ref ISSUES_CREATED: IntCounter = register_int_counter!(
+        "jira_issues_created_total",
+        "Number of Jira issues created from Discord threads"
+    )
+    .unwrap();
+    pub static ref ATTACHMENTS_UPLOADED: IntCounter = register_int_counter!(
+        "jira_attachments_uploaded_total",
+        "Number of Discord attachments uploaded to Jira issues"
+    )
+    .unwrap();
+    pub static ref WEBHOOKS_RECEIVED: IntCounterVec = register_int_counter_vec!(
+        "jira_webhooks_received_total",
+        "Number of Jira webhooks received, by webhook event",
+        &["event"]
+    )
+    .unwrap();
+    pub static ref ERRORS: IntCounterVec = register_int_counter_vec!(
+        "errors_total",
+        "Number of errors, by the kind of work that failed",
+        &["kind"]
+    )
+    .unwrap();
+    pub static ref JIRA_REQUEST_DURATION: HistogramVec = register_histogram_vec!(
+        "jira_request_duration_seconds",
+        "Latency of Jira API requests, by operation",
+        &["operation"]
+    )
+    .unwrap();
+    pub static ref DISCORD_REQUEST_DURATION: HistogramVec = register_histogram_vec!(
+        "discord_request_duration_seconds",
+        "Latency of Discord API requests, by operation",
+        &["operation"]
+    )
+    .unwrap();
+    pub static ref SYNC_QUEUE_DEPTH: IntGauge =
+        register_int_gauge!("sync_queue_depth", "Number of sync jobs currently running").unwrap();
+    pub static ref GATEWAY_CONNECTED: IntGauge = register_int_gauge!(
+        "gateway_connected",
+        "Whether the Discord gateway shard is connected (1) or not (0)"
+    )
+    .unwrap();
+}
+
+/// Awaits the request and records how long it took under the given operation label.
+pub async fn observe<F: IntoFuture>(
+    histogram: &HistogramVec,
+    operation: &str,
+    request: F,
+) -> F::Output {
+    let start = Instant::now();
+    let output = request.await;
+
+    histogram
+        .with_label_values(&[operation])
+        .observe(start.elapsed().as_secs_f64());
+
+    output
+}
+
+pub fn record_error(kind: &str) {
+    ERRORS.with_label_values(&[kind]).inc();
+}
@@ -8,6 +8,9 @@ use actix_web::{
     Error,
 };
 
+use tracing::Instrument;
+use uuid::Uuid;
+
 type LocalBoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + 'a>>;
 
 // There are two steps in middleware processing.
@@ -53,9 +56,23 @@ where
     forward_ready!(service);
 
     fn call(&self, req: ServiceRequest) -> Self::Future {
-        println!("req: {:?}", req.path());
-        println!("req: {:?}", req.method());
+        // reuse the caller's request id so a webhook can be traced across both systems
+        let request_id = req
+            .headers()
+            .get("x-request-id")
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_owned)
+            .unwrap_or_else(|| Uuid::new_v4().to_string());
+
+        let span = tracing::info_span!(
+            "webhook_request",
+            method = %req.method(),
+            path = %req.path(),
+            %request_id,
+        );
+        span.in_scope(|| tracing::info!("received request"));
+
         let fut = self.service.call(req);
-        Box::pin(fut)
+        Box::pin(fut.instrument(span))
     }
 }
@@ -1,10 +1,12 @@
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 use twilight_model::id::{
-    marker::{ChannelMarker, GuildMarker, MessageMarker},
+    marker::{ChannelMarker, GenericMarker, GuildMarker, MessageMarker, UserMarker},
     Id,
 };
 
-use crate::constants::StatusCategoryId;
+use crate::gateway::GatewayStatus;
 
 pub trait AsStr {
     fn as_str(&self) -> &'static str;
@@ -30,6 +32,9 @@ pub struct IssueFields {
     pub issuetype: IssueType,
     #[serde(rename = "statusCategory", skip_serializing_if = "Option::is_none")]
     pub status_category: Option<StatusCategory>,
+    /// custom field id (e.g. "customfield_10050") -> value
+    #[serde(flatten)]
+    pub custom_fields: HashMap<String, serde_json::Value>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -49,10 +54,22 @@ impl Default for Project {
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct IssueType {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub id: String,
 }
 
+impl IssueType {
+    /// Issue type looked up by name instead of id, e.g. "Bug" or "Task".
+    pub fn named(name: &str) -> Self {
+        IssueType {
+            name: Some(name.to_owned()),
+            id: String::new(),
+        }
+    }
+}
+
 impl Default for IssueType {
     fn default() -> Self {
         IssueType {
@@ -62,9 +79,10 @@ impl Default for IssueType {
     }
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct StatusCategory {
-    pub id: StatusCategoryId,
+    /// jira sends the category id as a number (2, 3 or 4), not one of the status ids in `StatusCategoryId`
+    pub id: u64,
     pub key: String,
     #[serde(rename = "colorName")]
     pub color_name: String,
@@ -150,6 +168,165 @@ impl AsStr for JiraMarkType {
     }
 }
 
+/// Issue as returned by `GET /rest/api/2/issue/{key}` (limited to the fields we ask for) and in webhooks.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct JiraIssueDetails {
+    pub key: String,
+    pub fields: JiraIssueDetailsFields,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct JiraIssueDetailsFields {
+    pub summary: String,
+    pub description: Option<String>,
+    pub status: Option<JiraStatus>,
+    pub assignee: Option<JiraUser>,
+    pub priority: Option<JiraNamedField>,
+    pub resolution: Option<JiraNamedField>,
+    #[serde(rename = "fixVersions", default)]
+    pub fix_versions: Vec<JiraNamedField>,
+    #[serde(default)]
+    pub issuelinks: Vec<JiraIssueLink>,
+    /// when the issue last changed, e.g. "2024-01-15T10:30:00.000+0000"
+    pub updated: Option<String>,
+}
+
+/// Response of `GET /rest/api/2/search`.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct JiraSearchResults {
+    #[serde(default)]
+    pub issues: Vec<JiraIssueDetails>,
+}
+
+/// Link to another issue, only one of `outward_issue` and `inward_issue` is set, depending
+/// on which side of the link the issue is on.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct JiraIssueLink {
+    #[serde(rename = "type")]
+    pub link_type: JiraIssueLinkType,
+    #[serde(rename = "outwardIssue")]
+    pub outward_issue: Option<JiraLinkedIssue>,
+    #[serde(rename = "inwardIssue")]
+    pub inward_issue: Option<JiraLinkedIssue>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct JiraIssueLinkType {
+    pub name: String,
+    /// how the issue with the outward issue describes it, e.g. "duplicates"
+    #[serde(default)]
+    pub outward: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct JiraLinkedIssue {
+    pub key: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct JiraStatus {
+    pub name: String,
+    #[serde(rename = "statusCategory")]
+    pub status_category: Option<StatusCategory>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct JiraUser {
+    #[serde(rename = "accountId")]
+    pub account_id: Option<String>,
+    #[serde(rename = "displayName")]
+    pub display_name: String,
+}
+
+/// Any Jira field that we only care about the name of, e.g. priorities, resolutions and versions.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct JiraNamedField {
+    pub name: String,
+}
+
+/// Transitions available for an issue in its current status.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct JiraTransitions {
+    pub transitions: Vec<JiraTransition>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct JiraTransition {
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct JiraWebhook {
+    #[serde(rename = "webhookEvent")]
+    pub webhook_event: String,
+    pub issue: Option<JiraIssueDetails>,
+    /// whoever made the change
+    pub user: Option<JiraUser>,
+    pub changelog: Option<JiraChangelog>,
+    /// set for comment events
+    pub comment: Option<JiraWebhookComment>,
+    /// set for issue link events
+    #[serde(rename = "issueLink")]
+    pub issue_link: Option<JiraWebhookIssueLink>,
+}
+
+/// Issue link in `issuelink_created` webhooks, which only have the ids of the linked issues.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct JiraWebhookIssueLink {
+    #[serde(rename = "sourceIssueId")]
+    pub source_issue_id: u64,
+    #[serde(rename = "destinationIssueId")]
+    pub destination_issue_id: u64,
+    #[serde(rename = "issueLinkType")]
+    pub link_type: JiraWebhookIssueLinkType,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct JiraWebhookIssueLinkType {
+    #[serde(rename = "outwardName", default)]
+    pub outward_name: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct JiraWebhookComment {
+    pub body: String,
+    pub author: Option<JiraUser>,
+    /// set when the comment is restricted to a group or project role
+    pub visibility: Option<JiraCommentVisibility>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct JiraCommentVisibility {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub value: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct JiraChangelog {
+    #[serde(default)]
+    pub items: Vec<JiraChangelogItem>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct JiraChangelogItem {
+    /// name of the changed field, e.g. "status" or "Fix Version"
+    pub field: String,
+    #[serde(rename = "fromString")]
+    pub from: Option<String>,
+    #[serde(rename = "toString")]
+    pub to: Option<String>,
+    /// id of the new value, e.g. of the resolution
+    #[serde(rename = "to")]
+    pub to_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct JiraComment {
+    pub body: String,
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct CreateJiraIssueResponse {
     pub key: String,
@@ -157,3 +334,57 @@ pub struct CreateJiraIssueResponse {
     #[serde(rename = "self")]
     pub url: String,
 }
+
+#[derive(Debug, Serialize)]
+pub struct CheckReport {
+    pub ok: bool,
+    pub error: Option<String>,
+}
+
+impl From<Result<(), String>> for CheckReport {
+    fn from(result: Result<(), String>) -> Self {
+        CheckReport {
+            ok: result.is_ok(),
+            error: result.err(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct LivenessReport {
+    pub healthy: bool,
+    pub gateway: GatewayStatus,
+    pub seconds_since_last_event: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReadinessReport {
+    pub ready: bool,
+    pub gateway: GatewayStatus,
+    pub seconds_since_last_event: Option<u64>,
+    pub jira: CheckReport,
+    pub storage: CheckReport,
+    pub queue_depth: usize,
+}
+
+/// A guild audit log, parsed by hand because twilight's model drops `applied_tags` changes.
+#[derive(Debug, Deserialize)]
+pub struct AuditLog {
+    pub audit_log_entries: Vec<AuditLogEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AuditLogEntry {
+    pub id: Id<GenericMarker>,
+    pub target_id: Option<Id<GenericMarker>>,
+    pub user_id: Option<Id<UserMarker>>,
+    #[serde(default)]
+    pub changes: Vec<AuditLogChange>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AuditLogChange {
+    pub key: String,
+    pub old_value: Option<serde_json::Value>,
+    pub new_value: Option<serde_json::Value>,
+}
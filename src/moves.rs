@@ -0,0 +1,77 @@
+use twilight_model::id::Id;
+
+use crate::{
+    context::Context, error::Result, models::JiraIssueDetails, status, subscriptions, utils,
+};
+
+/// Unlinks the threads of an issue that was deleted in Jira, so they can be synced again.
+pub async fn issue_deleted(ctx: &Context, issue: &JiraIssueDetails) -> Result<()> {
+    subscriptions::forget_issue(ctx, &issue.key)?;
+
+    let thread_ids = utils::threads_for_issue(ctx, issue).await?;
+
+    // orphaned links go too, there's nothing left for them to point at
+    for link in ctx.links.find_all_by_issue(&issue.key) {
+        ctx.links.remove(link.thread_id)?;
+    }
+
+    let message = format!(
+        "{} was deleted from our bug tracking system, so this thread is no longer tracked. Staff can sync it again if needed.",
+        issue.key
+    );
+    for thread_id in thread_ids {
+        utils::send_update_to_user_report(ctx, thread_id, &message).await?;
+        if let Some(tags) = ctx
+            .cache
+            .channel(Id::new(thread_id))
+            .and_then(|thread| thread.applied_tags.clone())
+        {
+            utils::remove_sync_tag(ctx, thread_id, &tags).await?;
+        }
+
+        tracing::info!(thread_id, jira_key = %issue.key, "unlinked deleted issue");
+    }
+
+    Ok(())
+}
+
+/// Points the threads of an issue that was moved, e.g. to another project, at its new key.
+pub async fn issue_moved(
+    ctx: &Context,
+    old_key: &str,
+    issue: &JiraIssueDetails,
+    changed_by: Option<&str>,
+) -> Result<()> {
+    subscriptions::rename_issue(ctx, old_key, &issue.key)?;
+
+    let links = ctx.links.find_all_by_issue(old_key);
+    let thread_ids = if links.is_empty() {
+        utils::threads_for_issue(ctx, issue).await?
+    } else {
+        for link in &links {
+            ctx.links
+                .update(link.thread_id, |link| link.issue_key = issue.key.clone())?;
+        }
+
+        links
+            .into_iter()
+            .filter(|link| !link.orphaned)
+            .map(|link| link.thread_id)
+            .collect()
+    };
+
+    let message = format!(
+        "{} was moved and is now tracked as the issue {}.",
+        old_key, issue.key
+    );
+    for thread_id in thread_ids {
+        utils::send_update_to_user_report(ctx, thread_id, &message).await?;
+
+        // the card shows the key, it's out of date now too
+        status::refresh_status_card(ctx, thread_id, issue, changed_by).await?;
+
+        tracing::info!(thread_id, old_key, jira_key = %issue.key, "issue moved");
+    }
+
+    Ok(())
+}
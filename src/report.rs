@@ -0,0 +1,168 @@
+use std::collections::HashMap;
+
+/// Sections of a structured bug report, as `(key, heading)`.
+///
+/// The key is what `JIRA_REPORT_FIELDS` maps to a Jira custom field, the heading is
+/// what the section is titled in the forum post and the Jira description.
+pub const SECTIONS: [(&str, &str); 4] = [
+    ("steps", "Steps to reproduce"),
+    ("expected_actual", "Expected vs actual"),
+    ("platform", "Platform"),
+    ("app_version", "App version"),
+];
+
+/// A bug report split into its sections, either from the `/report-bug` modal or
+/// parsed back out of a forum post that follows the same layout.
+#[derive(Debug, Default)]
+pub struct BugReport {
+    /// section key -> contents
+    pub sections: HashMap<String, String>,
+    /// anything before the first heading
+    pub preamble: String,
+}
+
+impl BugReport {
+    /// Parses a forum post made of `**Heading**` lines followed by their contents.
+    ///
+    /// Returns `None` if the post doesn't contain any of the known headings.
+    pub fn parse(content: &str) -> Option<Self> {
+        let mut report = BugReport::default();
+        let mut current: Option<&str> = None;
+
+        for line in content.lines() {
+            let heading = line
+                .trim()
+                .strip_prefix("**")
+                .and_then(|line| line.strip_suffix("**"))
+                .and_then(|heading| {
+                    SECTIONS
+                        .iter()
+                        .find(|(_, title)| title.eq_ignore_ascii_case(heading.trim()))
+                });
+
+            if let Some((key, _)) = heading {
+                current = Some(key);
+                report.sections.entry(key.to_string()).or_default();
+                continue;
+            }
+
+            let text = match current {
+                Some(key) => report.sections.entry(key.to_string()).or_default(),
+                None => &mut report.preamble,
+            };
+            text.push_str(line);
+            text.push('\n');
+        }
+
+        if report.sections.is_empty() {
+            return None;
+        }
+
+        report.preamble = report.preamble.trim().to_owned();
+        for text in report.sections.values_mut() {
+            *text = text.trim().to_owned();
+        }
+
+        Some(report)
+    }
+
+    pub fn section(&self, key: &str) -> Option<&str> {
+        self.sections
+            .get(key)
+            .map(String::as_str)
+            .filter(|text| !text.is_empty())
+    }
+
+    /// Content of the forum post for the report, using the same layout that [`BugReport::parse`] reads.
+    pub fn to_message_content(&self) -> String {
+        // the preamble goes first, anything after a heading would be read back as part of that section
+        let mut content = format!("{}\n\n", self.preamble);
+
+        for (key, heading) in SECTIONS {
+            if let Some(text) = self.section(key) {
+                content.push_str(&format!("**{}**\n{}\n\n", heading, text));
+            }
+        }
+
+        content.trim().to_owned()
+    }
+
+    /// Body of the Jira description for the report, using Jira's wiki markup for the headings.
+    pub fn to_jira_description(&self) -> String {
+        let mut description = String::new();
+
+        if !self.preamble.is_empty() {
+            description.push_str(&format!("{}\n\n", self.preamble));
+        }
+
+        for (key, heading) in SECTIONS {
+            if let Some(text) = self.section(key) {
+                description.push_str(&format!("h3. {}\n{}\n\n", heading, text));
+            }
+        }
+
+        description.trim().to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report() -> BugReport {
+        BugReport {
+            sections: [
+                ("steps", "1. Open the map\n2. Zoom out"),
+                ("platform", "Android"),
+            ]
+            .into_iter()
+            .map(|(key, text)| (key.to_owned(), text.to_owned()))
+            .collect(),
+            preamble: "Reported by <@123>".to_owned(),
+        }
+    }
+
+    #[test]
+    fn parses_headings_and_preamble() {
+        let report = BugReport::parse(
+            "Reported by <@123>\n\n**steps to reproduce**\n1. Open the map\n\n** Platform **\nAndroid\n",
+        )
+        .unwrap();
+
+        assert_eq!(report.preamble, "Reported by <@123>");
+        assert_eq!(report.section("steps"), Some("1. Open the map"));
+        assert_eq!(report.section("platform"), Some("Android"));
+        assert_eq!(report.section("app_version"), None);
+    }
+
+    #[test]
+    fn posts_without_known_headings_dont_parse() {
+        assert!(BugReport::parse("The map is broken\n**Not a heading**\nreally").is_none());
+    }
+
+    #[test]
+    fn empty_sections_are_skipped() {
+        let report = BugReport::parse("**Platform**\n\n**App version**\n1.2.3").unwrap();
+
+        assert_eq!(report.section("platform"), None);
+        assert_eq!(report.section("app_version"), Some("1.2.3"));
+        assert!(!report.to_message_content().contains("Platform"));
+    }
+
+    #[test]
+    fn message_content_round_trips() {
+        let report = report();
+        let parsed = BugReport::parse(&report.to_message_content()).unwrap();
+
+        assert_eq!(parsed.preamble, report.preamble);
+        assert_eq!(parsed.sections, report.sections);
+    }
+
+    #[test]
+    fn jira_description_uses_wiki_headings() {
+        assert_eq!(
+            report().to_jira_description(),
+            "Reported by <@123>\n\nh3. Steps to reproduce\n1. Open the map\n2. Zoom out\n\nh3. Platform\nAndroid"
+        );
+    }
+}
@@ -0,0 +1,318 @@
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use twilight_model::{
+    application::interaction::{modal::ModalInteractionData, Interaction},
+    channel::message::{
+        component::{ActionRow, ButtonStyle, TextInputStyle},
+        embed::{EmbedField, EmbedFooter},
+        Component, Embed,
+    },
+    http::interaction::InteractionResponseData,
+    id::Id,
+};
+
+use crate::{
+    constants,
+    context::Context,
+    error::{Error, Result},
+    interactions::{self, respond_ephemeral, respond_modal, text_input, update_message},
+    jobs::SyncJob,
+    metrics::{self, DISCORD_REQUEST_DURATION},
+    utils,
+};
+
+/// prefix of the approval card buttons' custom ids, followed by the action and thread id
+pub const REVIEW_BUTTON_ID: &str = "review";
+/// prefix of the edit modal's custom id, followed by the thread id
+pub const REVIEW_EDIT_MODAL_ID: &str = "review_edit";
+
+/// A bug report waiting for staff approval before its Jira issue is created.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct PendingReview {
+    pub thread_id: u64,
+    /// the approval card in the staff channel
+    pub card_message_id: u64,
+    pub summary: String,
+    /// body of the Jira description, without the report origin
+    pub description: String,
+    pub issue_type: Option<String>,
+}
+
+/// Posts an approval card for the thread in the staff channel, unless one is already waiting.
+pub async fn request_approval(ctx: &Context, thread_id: u64) -> Result<()> {
+    if ctx.reviews.contains(thread_id) {
+        return Ok(());
+    }
+
+    let staff_channel_id = constants::STAFF_CHANNEL_ID.ok_or_else(|| {
+        Error::Config("STAFF_CHANNEL_ID must be set when REVIEW_MODE is enabled".to_owned())
+    })?;
+
+    let message = utils::get_starter_message(ctx, thread_id).await?;
+    let channel = ctx.discord.channel(Id::new(thread_id));
+    let channel = metrics::observe(&DISCORD_REQUEST_DURATION, "get_channel", channel)
+        .await?
+        .model()
+        .await?;
+
+    let mut review = PendingReview {
+        thread_id,
+        card_message_id: 0,
+        summary: channel
+            .name
+            .unwrap_or_else(|| format!("Bug Report from Post ID: {}", thread_id)),
+        description: utils::report_description(&message.content),
+        issue_type: None,
+    };
+
+    let embeds = [card_embed(&review, "Waiting for approval")];
+    let components = card_buttons(thread_id);
+    let request = ctx
+        .discord
+        .create_message(Id::new(staff_channel_id))
+        .embeds(&embeds)?
+        .components(&components)?;
+    let card = metrics::observe(&DISCORD_REQUEST_DURATION, "create_message", request)
+        .await?
+        .model()
+        .await?;
+
+    review.card_message_id = card.id.get();
+    ctx.reviews.insert(thread_id, review)?;
+
+    tracing::info!(thread_id, "bug report waiting for approval");
+
+    utils::send_update_to_user_report(
+        ctx,
+        thread_id,
+        "Thanks! Staff will review this report before it's added to our bug tracking system.",
+    )
+    .await
+}
+
+fn card_embed(review: &PendingReview, status: &str) -> Embed {
+    Embed {
+        author: None,
+        color: None,
+        // embed descriptions are capped at 4096 characters
+        description: Some(review.description.chars().take(4000).collect()),
+        fields: vec![
+            EmbedField {
+                inline: true,
+                name: "Issue type".to_owned(),
+                value: review
+                    .issue_type
+                    .clone()
+                    .unwrap_or_else(|| "Default".to_owned()),
+            },
+            EmbedField {
+                inline: true,
+                name: "Thread".to_owned(),
+                value: format!("<#{}>", review.thread_id),
+            },
+        ],
+        footer: Some(EmbedFooter {
+            icon_url: None,
+            proxy_icon_url: None,
+            text: status.to_owned(),
+        }),
+        image: None,
+        kind: "rich".to_owned(),
+        provider: None,
+        thumbnail: None,
+        timestamp: None,
+        title: Some(review.summary.clone()),
+        url: Some(utils::thread_url(review.thread_id)),
+        video: None,
+    }
+}
+
+fn card_buttons(thread_id: u64) -> Vec<Component> {
+    let custom_id = |action: &str| format!("{}:{}:{}", REVIEW_BUTTON_ID, action, thread_id);
+
+    vec![Component::ActionRow(ActionRow {
+        components: vec![
+            interactions::button(custom_id("approve"), "Approve", ButtonStyle::Success),
+            interactions::button(custom_id("edit"), "Edit", ButtonStyle::Secondary),
+            interactions::button(custom_id("reject"), "Reject", ButtonStyle::Danger),
+        ],
+    })]
+}
+
+/// Name and id of the staff member reviewing, as recorded in the Jira issue.
+fn reviewer(interaction: &Interaction) -> String {
+    match interaction.author() {
+        Some(user) => format!("{} ({})", user.name, user.id),
+        None => "unknown".to_owned(),
+    }
+}
+
+/// Handles the Approve / Edit / Reject buttons on an approval card.
+pub async fn handle_button(
+    ctx: &Arc<Context>,
+    interaction: &Interaction,
+    target: &str,
+) -> Result<()> {
+    if !interactions::is_staff(interaction) {
+        return respond_ephemeral(ctx, interaction, "Only staff can review bug reports.").await;
+    }
+
+    let (action, thread_id) = target
+        .split_once(':')
+        .ok_or_else(|| Error::Parse(format!("invalid review target: {}", target)))?;
+    let thread_id = utils::parse_thread_id(thread_id, "review")?;
+
+    let review = match ctx.reviews.get(thread_id) {
+        Some(review) => review,
+        None => {
+            return respond_ephemeral(ctx, interaction, "This report was already reviewed.").await
+        }
+    };
+
+    match action {
+        "approve" => {
+            ctx.reviews.remove(thread_id)?;
+
+            let approved_by = reviewer(interaction);
+            tracing::info!(thread_id, %approved_by, "bug report approved");
+
+            ctx.jobs.spawn(
+                ctx,
+                SyncJob {
+                    issue_type: review.issue_type.clone(),
+                    summary: Some(review.summary.clone()),
+                    description: Some(review.description.clone()),
+                    approved_by: Some(approved_by.clone()),
+                    ..SyncJob::new(thread_id)
+                },
+            );
+
+            let status = format!("Approved by {}", approved_by);
+            close_card(ctx, interaction, &review, &status).await
+        }
+        "edit" => {
+            respond_modal(
+                ctx,
+                interaction,
+                format!("{}:{}", REVIEW_EDIT_MODAL_ID, thread_id),
+                "Edit Jira issue",
+                vec![
+                    text_input(
+                        "summary",
+                        "Summary",
+                        TextInputStyle::Short,
+                        255,
+                        true,
+                        Some(review.summary),
+                    ),
+                    text_input(
+                        "description",
+                        "Description",
+                        TextInputStyle::Paragraph,
+                        4000,
+                        false,
+                        Some(review.description.chars().take(4000).collect()),
+                    ),
+                    text_input(
+                        "issue_type",
+                        "Issue type",
+                        TextInputStyle::Short,
+                        50,
+                        false,
+                        review.issue_type,
+                    ),
+                ],
+            )
+            .await
+        }
+        "reject" => {
+            ctx.reviews.remove(thread_id)?;
+
+            let rejected_by = reviewer(interaction);
+            tracing::info!(thread_id, %rejected_by, "bug report rejected");
+
+            // take the sync tag back off so the report can be sent for review again later
+            if let Some(tags) = ctx
+                .cache
+                .channel(Id::new(thread_id))
+                .and_then(|thread| thread.applied_tags.clone())
+            {
+                utils::remove_sync_tag(ctx, thread_id, &tags).await?;
+            }
+
+            utils::send_update_to_user_report(
+                ctx,
+                thread_id,
+                "Staff reviewed this report and decided not to add it to our bug tracking system for now.",
+            )
+            .await?;
+
+            let status = format!("Rejected by {}", rejected_by);
+            close_card(ctx, interaction, &review, &status).await
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Updates the card with the outcome of the review and removes its buttons.
+async fn close_card(
+    ctx: &Context,
+    interaction: &Interaction,
+    review: &PendingReview,
+    status: &str,
+) -> Result<()> {
+    update_message(
+        ctx,
+        interaction,
+        InteractionResponseData {
+            embeds: Some(vec![card_embed(review, status)]),
+            components: Some(Vec::new()),
+            ..Default::default()
+        },
+    )
+    .await
+}
+
+/// Saves the changes from the edit modal and refreshes the approval card.
+pub async fn handle_edit_modal(
+    ctx: &Context,
+    interaction: &Interaction,
+    data: &ModalInteractionData,
+    target: &str,
+) -> Result<()> {
+    if !interactions::is_staff(interaction) {
+        return respond_ephemeral(ctx, interaction, "Only staff can review bug reports.").await;
+    }
+
+    let thread_id = utils::parse_thread_id(target, "review")?;
+
+    let mut review = match ctx.reviews.get(thread_id) {
+        Some(review) => review,
+        None => {
+            return respond_ephemeral(ctx, interaction, "This report was already reviewed.").await
+        }
+    };
+
+    if let Some(summary) = interactions::modal_value(data, "summary") {
+        review.summary = summary.to_owned();
+    }
+    review.description = interactions::modal_value(data, "description")
+        .unwrap_or_default()
+        .to_owned();
+    review.issue_type = interactions::modal_value(data, "issue_type").map(str::to_owned);
+
+    ctx.reviews.insert(thread_id, review.clone())?;
+
+    let status = format!("Waiting for approval, edited by {}", reviewer(interaction));
+    update_message(
+        ctx,
+        interaction,
+        InteractionResponseData {
+            embeds: Some(vec![card_embed(&review, &status)]),
+            ..Default::default()
+        },
+    )
+    .await
+}
@@ -0,0 +1,388 @@
+use std::collections::HashSet;
+
+use reqwest::Method;
+use twilight_model::{
+    application::interaction::Interaction,
+    channel::{
+        message::{
+            component::{ActionRow, ButtonStyle},
+            Component,
+        },
+        Channel,
+    },
+    id::Id,
+};
+
+use crate::{
+    commands, constants,
+    context::Context,
+    error::{Error, Result},
+    interactions::{self, defer_ephemeral, respond_ephemeral, update_deferred},
+    metrics::{self, DISCORD_REQUEST_DURATION},
+    models::{JiraIssueDetails, JiraSearchResults, Project},
+    status,
+    storage::IssueLink,
+    utils,
+};
+
+/// prefix of the "This is my bug" buttons' custom ids, followed by the thread id and issue key
+pub const SIMILAR_BUTTON_ID: &str = "similar";
+
+/// words too common in bug reports to say anything about whether two are alike
+const STOP_WORDS: [&str; 20] = [
+    "the", "and", "for", "that", "this", "with", "when", "from", "have", "are", "was", "but",
+    "not", "can", "after", "bug", "issue", "does", "doesn", "there",
+];
+
+/// Lowercase words of at least three characters, without stop words.
+fn words(text: &str) -> HashSet<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| word.chars().count() >= 3)
+        .map(str::to_lowercase)
+        .filter(|word| !STOP_WORDS.contains(&word.as_str()))
+        .collect()
+}
+
+/// Share of the words in either text that are in both.
+fn jaccard(a: &HashSet<String>, b: &HashSet<String>) -> f64 {
+    let union = a.union(b).count();
+
+    if union == 0 {
+        return 0.0;
+    }
+
+    a.intersection(b).count() as f64 / union as f64
+}
+
+/// Keys of previously synced threads that look like the new report, most similar first.
+/// Titles count for more than descriptions since they're usually the symptom in a nutshell.
+fn local_matches(
+    links: Vec<IssueLink>,
+    title: &str,
+    description: &str,
+    threshold: f64,
+) -> Vec<String> {
+    let title = words(title);
+    let description = words(description);
+
+    let mut matches = links
+        .into_iter()
+        .filter_map(|link| {
+            // links made before titles were stored can still match on their description
+            let score = 0.6 * jaccard(&title, &words(link.title.as_deref().unwrap_or("")))
+                + 0.4
+                    * jaccard(
+                        &description,
+                        &words(link.description.as_deref().unwrap_or("")),
+                    );
+
+            (score >= threshold).then_some((score, link.issue_key))
+        })
+        .collect::<Vec<_>>();
+
+    matches.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+
+    // an issue can be linked to several threads, it only needs suggesting once
+    let mut keys = Vec::<String>::new();
+    for (_, key) in matches {
+        if !keys.contains(&key) {
+            keys.push(key);
+        }
+    }
+    keys
+}
+
+/// Issues Jira's text search finds for the title of the new report.
+async fn jira_matches(ctx: &Context, title: &str) -> Result<Vec<JiraIssueDetails>> {
+    // only plain words, so nothing in the title can break out of the query
+    let terms = words(title)
+        .into_iter()
+        .take(8)
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    if terms.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut jql = format!(
+        r#"project = "{}" AND text ~ "{}""#,
+        Project::default().key,
+        terms
+    );
+    if let Some(filter) = constants::SIMILAR_ISSUES_JQL.as_ref() {
+        jql.push_str(&format!(" AND ({})", filter));
+    }
+
+    let request = ctx.jira.request(Method::GET, "search").query(&[
+        ("jql", jql.as_str()),
+        ("maxResults", &constants::SIMILAR_ISSUES_LIMIT.to_string()),
+        ("fields", "summary,status"),
+    ]);
+
+    Ok(ctx
+        .jira
+        .send("search", request)
+        .await?
+        .json::<JiraSearchResults>()
+        .await
+        .map_err(|error| Error::Parse(error.to_string()))?
+        .issues)
+}
+
+/// Replies to a new bug report with issues that might already cover it, each with a button
+/// to follow the existing issue instead of syncing a new one.
+pub async fn suggest_similar_issues(ctx: &Context, thread: &Channel) -> Result<()> {
+    let thread_id = thread.id.get();
+
+    // posts made through the bot are synced right away, there's no point suggesting anything.
+    // the thread can arrive before their sync job is queued, so go by who owns it too
+    let posted_by_bot = thread
+        .owner_id
+        .is_some_and(|owner_id| ctx.bot_user_id.get() == Some(&owner_id.get()));
+    if thread.parent_id != Some(Id::new(*constants::BUG_REPORT_CHANNEL_ID))
+        || thread.newly_created != Some(true)
+        || *constants::SIMILAR_ISSUES_LIMIT == 0
+        || posted_by_bot
+        || ctx.links.get(thread_id).is_some()
+        || ctx.jobs.get(thread_id).is_some()
+    {
+        return Ok(());
+    }
+
+    let title = thread.name.clone().unwrap_or_default();
+    let message = utils::get_starter_message(ctx, thread_id).await?;
+
+    let mut issues = Vec::new();
+    for key in local_matches(
+        ctx.links.all(),
+        &title,
+        &message.content,
+        *constants::SIMILARITY_THRESHOLD,
+    )
+    .into_iter()
+    .take(*constants::SIMILAR_ISSUES_LIMIT)
+    {
+        match utils::get_jira_issue(ctx, &key).await {
+            Ok(issue) => issues.push(issue),
+            Err(error) if error.is_not_found() => {}
+            Err(error) => return Err(error),
+        }
+    }
+    for issue in jira_matches(ctx, &title).await? {
+        if !issues.iter().any(|found| found.key == issue.key) {
+            issues.push(issue);
+        }
+    }
+    issues.truncate(*constants::SIMILAR_ISSUES_LIMIT);
+
+    if issues.is_empty() {
+        return Ok(());
+    }
+
+    let mut content = "This might already be reported:".to_owned();
+    for issue in &issues {
+        let status = issue
+            .fields
+            .status
+            .as_ref()
+            .map_or("Unknown", |status| status.name.as_str());
+        content.push_str(&format!(
+            "\n- **{}**: {} ({})",
+            issue.key, issue.fields.summary, status
+        ));
+
+        match ctx.links.find_by_issue(&issue.key) {
+            Some(link) if !link.orphaned => {
+                content.push_str(&format!(", discussed in <#{}>", link.thread_id))
+            }
+            _ => content.push_str(&format!(", <{}>", utils::issue_url(&issue.key))),
+        }
+    }
+    content.push_str("\nIf one of these is your bug, press its button to follow that issue instead of opening a new one.");
+
+    let content = utils::truncate_message(content);
+
+    // an action row holds at most five buttons
+    let components = [Component::ActionRow(ActionRow {
+        components: issues
+            .iter()
+            .take(5)
+            .map(|issue| {
+                interactions::button(
+                    format!("{}:{}:{}", SIMILAR_BUTTON_ID, thread_id, issue.key),
+                    &format!("This is my bug: {}", issue.key),
+                    ButtonStyle::Secondary,
+                )
+            })
+            .collect(),
+    })];
+    let request = ctx
+        .discord
+        .create_message(Id::new(thread_id))
+        .content(&content)?
+        .components(&components)?;
+    metrics::observe(&DISCORD_REQUEST_DURATION, "create_message", request).await?;
+
+    tracing::info!(thread_id, count = issues.len(), "suggested similar issues");
+
+    Ok(())
+}
+
+/// Links the thread to the issue the reporter picked, instead of creating a new one.
+pub async fn handle_button(ctx: &Context, interaction: &Interaction, target: &str) -> Result<()> {
+    let (thread_id, issue_key) = target
+        .split_once(':')
+        .and_then(|(thread_id, issue_key)| Some((thread_id.parse::<u64>().ok()?, issue_key)))
+        .ok_or_else(|| Error::Parse(format!("invalid similar issue target: {}", target)))?;
+
+    if let Some(link) = ctx.links.get(thread_id) {
+        let content = format!("This thread is already linked to {}.", link.issue_key);
+        return respond_ephemeral(ctx, interaction, &content).await;
+    }
+
+    // linking now would race the job into creating a second issue
+    if ctx.jobs.get(thread_id).is_some() {
+        return respond_ephemeral(
+            ctx,
+            interaction,
+            "This thread is already being synced to Jira.",
+        )
+        .await;
+    }
+
+    defer_ephemeral(ctx, interaction).await?;
+
+    // only the reporter gets to decide it's the same bug, or staff on their behalf
+    let message = utils::get_starter_message(ctx, thread_id).await?;
+    let user_id = interaction.author_id().map(|user_id| user_id.get());
+    if !interactions::is_staff(interaction) && user_id != status::reporter_id(ctx, &message) {
+        return update_deferred(
+            ctx,
+            interaction,
+            "Only the person who reported this bug can pick an existing issue for it.",
+        )
+        .await;
+    }
+
+    let title = ctx
+        .cache
+        .channel(Id::new(thread_id))
+        .and_then(|thread| thread.name.clone());
+    commands::link_thread(ctx, thread_id, issue_key, title).await?;
+
+    tracing::info!(
+        thread_id,
+        jira_key = issue_key,
+        "linked thread to similar issue"
+    );
+
+    // the other suggestions are moot now
+    if let Some(suggestions) = &interaction.message {
+        let request = ctx
+            .discord
+            .update_message(suggestions.channel_id, suggestions.id)
+            .components(Some(&[]))?;
+        metrics::observe(&DISCORD_REQUEST_DURATION, "update_message", request).await?;
+    }
+
+    let content = format!("Linked this thread to {}.", issue_key);
+    update_deferred(ctx, interaction, &content).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn link(thread_id: u64, issue_key: &str, title: Option<&str>, description: &str) -> IssueLink {
+        IssueLink {
+            thread_id,
+            issue_key: issue_key.to_owned(),
+            title: title.map(str::to_owned),
+            description: Some(description.to_owned()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn words_skip_short_and_stop_words() {
+        let expected = ["game", "crashes", "loading", "map"]
+            .into_iter()
+            .map(str::to_owned)
+            .collect::<HashSet<_>>();
+
+        assert_eq!(words("The game CRASHES when loading a map!"), expected);
+    }
+
+    #[test]
+    fn jaccard_of_overlapping_sets() {
+        let a = words("game crashes loading");
+        let b = words("game freezes loading");
+
+        assert_eq!(jaccard(&a, &b), 0.5);
+        assert_eq!(jaccard(&a, &a), 1.0);
+        assert_eq!(jaccard(&a, &words("audio stutters")), 0.0);
+    }
+
+    #[test]
+    fn jaccard_of_nothing_is_zero() {
+        assert_eq!(jaccard(&HashSet::new(), &HashSet::new()), 0.0);
+    }
+
+    #[test]
+    fn matches_most_similar_first() {
+        let links = vec![
+            link(
+                1,
+                "BUG-1",
+                Some("Game crashes on startup"),
+                "audio stutters",
+            ),
+            link(
+                2,
+                "BUG-2",
+                Some("Game crashes loading map"),
+                "black screen then crash",
+            ),
+            link(
+                3,
+                "BUG-3",
+                Some("Wrong font in settings"),
+                "letters are tiny",
+            ),
+        ];
+
+        assert_eq!(
+            local_matches(
+                links,
+                "Game crashes loading map",
+                "black screen then crash",
+                0.2
+            ),
+            ["BUG-2", "BUG-1"]
+        );
+    }
+
+    #[test]
+    fn matches_each_issue_once() {
+        let links = vec![
+            link(1, "BUG-1", Some("Game crashes loading map"), ""),
+            link(2, "BUG-1", Some("Game crashes loading map"), ""),
+        ];
+
+        assert_eq!(
+            local_matches(links, "Game crashes loading map", "", 0.3),
+            ["BUG-1"]
+        );
+    }
+
+    #[test]
+    fn matches_links_without_a_title_by_description() {
+        let links = vec![link(1, "BUG-1", None, "black screen after loading the map")];
+
+        assert_eq!(
+            local_matches(links, "Crash", "black screen after loading the map", 0.3),
+            ["BUG-1"]
+        );
+    }
+}
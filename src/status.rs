@@ -0,0 +1,170 @@
+use twilight_model::{
+    channel::Message,
+    id::{
+        marker::{ChannelMarker, MessageMarker},
+        Id,
+    },
+};
+
+use crate::{
+    constants,
+    context::Context,
+    embeds,
+    error::{Error, Result},
+    feedback,
+    metrics::{self, DISCORD_REQUEST_DURATION},
+    models::{JiraChangelogItem, JiraIssueDetails},
+    utils,
+};
+
+/// Who reported the bug in the thread, given its starter message. Posts the bot made on
+/// someone's behalf have their reporter recorded when they're created.
+pub fn reporter_id(ctx: &Context, message: &Message) -> Option<u64> {
+    if message.author.bot {
+        ctx.post_reporters.get(message.channel_id.get())
+    } else {
+        Some(message.author.id.get())
+    }
+}
+
+/// Posts and pins the live status card for a linked thread, unless it already has one.
+pub async fn post_status_card(ctx: &Context, thread_id: u64) -> Result<()> {
+    let link = match ctx.links.get(thread_id) {
+        Some(link) if link.status_message_id.is_none() => link,
+        _ => return Ok(()),
+    };
+
+    let issue = utils::get_jira_issue(ctx, &link.issue_key).await?;
+    create_status_card(ctx, thread_id, &issue, None).await
+}
+
+async fn create_status_card(
+    ctx: &Context,
+    thread_id: u64,
+    issue: &JiraIssueDetails,
+    changed_by: Option<&str>,
+) -> Result<()> {
+    // without a link the card's id can't be kept, so every update would post a new card
+    if ctx.links.get(thread_id).is_none() {
+        return Ok(());
+    }
+
+    let channel_id = Id::<ChannelMarker>::new(thread_id);
+
+    let embeds = [embeds::issue_embed(issue, changed_by)];
+    let text = embeds::issue_text(issue, changed_by);
+    let components = feedback::feedback_buttons(thread_id);
+    let request = ctx
+        .discord
+        .create_message(channel_id)
+        .components(&components)?;
+    let request = if *constants::PLAIN_TEXT_UPDATES {
+        request.content(&text)?
+    } else {
+        request.embeds(&embeds)?
+    };
+    let card = metrics::observe(&DISCORD_REQUEST_DURATION, "create_message", request)
+        .await?
+        .model()
+        .await?;
+
+    // saved before pinning, a card that couldn't be pinned is still edited from then on
+    ctx.links.update(thread_id, |link| {
+        link.status_message_id = Some(card.id.get())
+    })?;
+
+    let pin = ctx.discord.create_pin(channel_id, card.id);
+    metrics::observe(&DISCORD_REQUEST_DURATION, "create_pin", pin).await?;
+
+    tracing::info!(thread_id, jira_key = %issue.key, "posted status card");
+
+    Ok(())
+}
+
+/// Edits the thread's status card to show the current state of the issue, posting a new
+/// card if the thread doesn't have one or it was deleted.
+pub async fn refresh_status_card(
+    ctx: &Context,
+    thread_id: u64,
+    issue: &JiraIssueDetails,
+    changed_by: Option<&str>,
+) -> Result<()> {
+    let status_message_id = ctx
+        .links
+        .get(thread_id)
+        .and_then(|link| link.status_message_id);
+
+    let status_message_id = match status_message_id {
+        Some(status_message_id) => Id::<MessageMarker>::new(status_message_id),
+        None => return create_status_card(ctx, thread_id, issue, changed_by).await,
+    };
+
+    let embeds = [embeds::issue_embed(issue, changed_by)];
+    let text = embeds::issue_text(issue, changed_by);
+    let request = ctx
+        .discord
+        .update_message(Id::new(thread_id), status_message_id);
+    let request = if *constants::PLAIN_TEXT_UPDATES {
+        request.content(Some(&text))?
+    } else {
+        request.embeds(Some(&embeds))?
+    };
+
+    match metrics::observe(&DISCORD_REQUEST_DURATION, "update_message", request)
+        .await
+        .map_err(Error::from)
+    {
+        Ok(_) => {
+            tracing::info!(thread_id, jira_key = %issue.key, "updated status card");
+            Ok(())
+        }
+        Err(error) if error.is_not_found() => {
+            create_status_card(ctx, thread_id, issue, changed_by).await
+        }
+        Err(error) => Err(error),
+    }
+}
+
+/// Message for a change the reporter should hear about directly, rather than just
+/// through the status card.
+pub fn notable_transition(items: &[JiraChangelogItem]) -> Option<String> {
+    items.iter().find_map(|item| match item.field.as_str() {
+        "resolution" => Some(match &item.to {
+            Some(resolution) => format!("This issue was resolved as **{}**.", resolution),
+            None => "This issue was reopened.".to_owned(),
+        }),
+        "status"
+            if item.to.as_deref().is_some_and(|status| {
+                constants::NEEDS_INFO_STATUSES
+                    .iter()
+                    .any(|needs_info| needs_info.eq_ignore_ascii_case(status))
+            }) =>
+        {
+            Some(
+                "We need more information from you to continue with this issue, please reply here with any details you can share."
+                    .to_owned(),
+            )
+        }
+        _ => None,
+    })
+}
+
+/// Posts a separate message in the thread that pings the reporter.
+pub async fn notify_reporter(ctx: &Context, thread_id: u64, message: &str) -> Result<()> {
+    let reporter_id = ctx.links.get(thread_id).and_then(|link| link.reporter_id);
+
+    let content = match reporter_id {
+        Some(reporter_id) => format!("<@{}> {}", reporter_id, message),
+        None => message.to_owned(),
+    };
+
+    let components = feedback::feedback_buttons(thread_id);
+    let request = ctx
+        .discord
+        .create_message(Id::new(thread_id))
+        .content(&content)?
+        .components(&components)?;
+    metrics::observe(&DISCORD_REQUEST_DURATION, "create_message", request).await?;
+
+    Ok(())
+}
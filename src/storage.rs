@@ -0,0 +1,349 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{affected::AffectedUser, constants, error::Result};
+
+/// Link between a bug report thread and the Jira issue tracking it.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct IssueLink {
+    pub thread_id: u64,
+    pub issue_key: String,
+    /// who reported the bug, pinged about notable changes to the issue
+    pub reporter_id: Option<u64>,
+    /// the pinned status card that is edited whenever the issue changes
+    pub status_message_id: Option<u64>,
+    /// followers saved by older versions, moved to the subscriber store on startup
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub subscribers: Vec<u64>,
+    /// users who said they're affected by the bug too
+    pub affected_users: Vec<AffectedUser>,
+    /// highest affected count that already raised the issue's priority
+    pub priority_threshold: usize,
+    /// thread name and issue summary as of the last sync between them
+    pub title: Option<String>,
+    /// the reporter's part of the issue description, for finding similar reports
+    pub description: Option<String>,
+    /// staff rewrote the description during review, so reporter edits only get a comment
+    pub description_reviewed: bool,
+    /// the thread or its starter message was deleted, updates aren't posted there anymore
+    pub orphaned: bool,
+    /// key of the issue this one was marked as a duplicate of
+    pub duplicate_of: Option<String>,
+    /// primary thread of the issue, for threads linked to an issue that already had one.
+    /// Issue updates are posted in every linked thread, but only the primary one's title and
+    /// starter message are synced with the issue
+    pub follows: Option<u64>,
+}
+
+/// Thread -> issue links, kept in memory and written through to a JSON file in the data directory.
+pub struct LinkStore {
+    path: PathBuf,
+    links: Mutex<HashMap<u64, IssueLink>>,
+}
+
+impl LinkStore {
+    pub fn load() -> Result<Self> {
+        let path = Path::new(constants::DATA_DIR.as_str()).join("links.json");
+        let mut links = read_json::<Vec<IssueLink>>(&path)?
+            .unwrap_or_default()
+            .into_iter()
+            .map(|link| (link.thread_id, link))
+            .collect::<HashMap<_, _>>();
+
+        // older versions could link several threads to an issue without picking a primary one
+        let issue_keys = links
+            .values()
+            .map(|link| link.issue_key.clone())
+            .collect::<HashSet<_>>();
+        for issue_key in issue_keys {
+            assign_primary(&mut links, &issue_key);
+        }
+
+        Ok(LinkStore {
+            path,
+            links: Mutex::new(links),
+        })
+    }
+
+    pub fn get(&self, thread_id: u64) -> Option<IssueLink> {
+        self.links.lock().unwrap().get(&thread_id).cloned()
+    }
+
+    pub fn all(&self) -> Vec<IssueLink> {
+        self.links.lock().unwrap().values().cloned().collect()
+    }
+
+    /// The issue's primary thread link.
+    pub fn find_by_issue(&self, issue_key: &str) -> Option<IssueLink> {
+        self.links
+            .lock()
+            .unwrap()
+            .values()
+            .find(|link| link.issue_key == issue_key && link.follows.is_none())
+            .cloned()
+    }
+
+    /// Every thread link of the issue, the primary one first.
+    pub fn find_all_by_issue(&self, issue_key: &str) -> Vec<IssueLink> {
+        let mut links = self
+            .links
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|link| link.issue_key == issue_key)
+            .cloned()
+            .collect::<Vec<_>>();
+
+        links.sort_by_key(|link| (link.follows.is_some(), link.thread_id));
+        links
+    }
+
+    /// Stores the link, as a follower if the issue already has a primary thread.
+    pub fn insert(&self, mut link: IssueLink) -> Result<()> {
+        let mut links = self.links.lock().unwrap();
+
+        link.follows = links
+            .values()
+            .find(|other| {
+                other.issue_key == link.issue_key
+                    && other.follows.is_none()
+                    && other.thread_id != link.thread_id
+            })
+            .map(|primary| primary.thread_id);
+        let issue_key = link.issue_key.clone();
+        let replaced = links.insert(link.thread_id, link);
+
+        assign_primary(&mut links, &issue_key);
+        if let Some(replaced) = replaced.filter(|replaced| replaced.issue_key != issue_key) {
+            assign_primary(&mut links, &replaced.issue_key);
+        }
+
+        self.save(&links)
+    }
+
+    pub fn update(&self, thread_id: u64, update: impl FnOnce(&mut IssueLink)) -> Result<()> {
+        let mut links = self.links.lock().unwrap();
+
+        let (old_key, new_key) = match links.get_mut(&thread_id) {
+            Some(link) => {
+                let old_key = link.issue_key.clone();
+                update(link);
+                (old_key, link.issue_key.clone())
+            }
+            None => return Ok(()),
+        };
+
+        assign_primary(&mut links, &old_key);
+        if new_key != old_key {
+            assign_primary(&mut links, &new_key);
+        }
+
+        self.save(&links)
+    }
+
+    /// Removes the link, a follower takes over if it was the issue's primary thread.
+    pub fn remove(&self, thread_id: u64) -> Result<Option<IssueLink>> {
+        let mut links = self.links.lock().unwrap();
+        let removed = links.remove(&thread_id);
+
+        if let Some(removed) = &removed {
+            assign_primary(&mut links, &removed.issue_key);
+        }
+
+        self.save(&links)?;
+
+        Ok(removed)
+    }
+
+    fn save(&self, links: &HashMap<u64, IssueLink>) -> Result<()> {
+        write_json(&self.path, &links.values().collect::<Vec<_>>())
+    }
+}
+
+/// Keeps exactly one primary thread per issue: the current primary while its thread is still
+/// around, else the oldest thread that is. Every other thread follows it.
+fn assign_primary(links: &mut HashMap<u64, IssueLink>, issue_key: &str) {
+    let primary = links
+        .values()
+        .filter(|link| link.issue_key == issue_key)
+        .min_by_key(|link| (link.orphaned, link.follows.is_some(), link.thread_id))
+        .map(|link| link.thread_id);
+
+    let primary = match primary {
+        Some(primary) => primary,
+        None => return,
+    };
+
+    for link in links
+        .values_mut()
+        .filter(|link| link.issue_key == issue_key)
+    {
+        link.follows = (link.thread_id != primary).then_some(primary);
+    }
+}
+
+/// Small id -> value map, kept in memory and written through to a JSON file in the data directory.
+pub struct JsonMap<V> {
+    path: PathBuf,
+    entries: Mutex<HashMap<u64, V>>,
+}
+
+impl<V: Clone + Serialize + DeserializeOwned> JsonMap<V> {
+    pub fn load(file_name: &str) -> Result<Self> {
+        let path = Path::new(constants::DATA_DIR.as_str()).join(file_name);
+        let entries = read_json(&path)?.unwrap_or_default();
+
+        Ok(JsonMap {
+            path,
+            entries: Mutex::new(entries),
+        })
+    }
+
+    pub fn get(&self, id: u64) -> Option<V> {
+        self.entries.lock().unwrap().get(&id).cloned()
+    }
+
+    pub fn contains(&self, id: u64) -> bool {
+        self.entries.lock().unwrap().contains_key(&id)
+    }
+
+    pub fn insert(&self, id: u64, value: V) -> Result<()> {
+        let mut entries = self.entries.lock().unwrap();
+        entries.insert(id, value);
+
+        write_json(&self.path, &*entries)
+    }
+
+    pub fn remove(&self, id: u64) -> Result<Option<V>> {
+        let mut entries = self.entries.lock().unwrap();
+        let removed = entries.remove(&id);
+
+        if removed.is_some() {
+            write_json(&self.path, &*entries)?;
+        }
+
+        Ok(removed)
+    }
+
+    /// All entries, e.g. to find the ones matching some condition.
+    pub fn entries(&self) -> Vec<(u64, V)> {
+        let entries = self.entries.lock().unwrap();
+        entries
+            .iter()
+            .map(|(id, value)| (*id, value.clone()))
+            .collect()
+    }
+}
+
+impl<V: Clone + Default + Serialize + DeserializeOwned> JsonMap<V> {
+    /// Changes the value for the id in place, starting from the default if there isn't one yet.
+    pub fn upsert(&self, id: u64, f: impl FnOnce(&mut V)) -> Result<()> {
+        let mut entries = self.entries.lock().unwrap();
+        f(entries.entry(id).or_default());
+
+        write_json(&self.path, &*entries)
+    }
+}
+
+/// Reads a JSON file, returning `None` if it doesn't exist yet.
+pub fn read_json<T: DeserializeOwned>(path: &Path) -> Result<Option<T>> {
+    match std::fs::read_to_string(path) {
+        Ok(contents) => Ok(Some(serde_json::from_str(&contents)?)),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(error.into()),
+    }
+}
+
+/// Writes a JSON file through a temporary file so a crash never leaves it half written.
+pub fn write_json<T: Serialize + ?Sized>(path: &Path, value: &T) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let temporary_path = path.with_extension("json.tmp");
+    std::fs::write(&temporary_path, serde_json::to_string_pretty(value)?)?;
+    std::fs::rename(temporary_path, path)?;
+
+    Ok(())
+}
+
+/// Checks that the data directory exists (creating it if needed) and is writable.
+pub fn check_health() -> std::result::Result<(), String> {
+    let data_dir = Path::new(constants::DATA_DIR.as_str());
+    let probe = data_dir.join(".healthcheck");
+
+    std::fs::create_dir_all(data_dir)
+        .and_then(|_| std::fs::write(&probe, b"ok"))
+        .and_then(|_| std::fs::remove_file(&probe))
+        .map_err(|error| error.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn links(entries: &[(u64, &str, bool)]) -> HashMap<u64, IssueLink> {
+        entries
+            .iter()
+            .map(|&(thread_id, issue_key, orphaned)| {
+                let link = IssueLink {
+                    thread_id,
+                    issue_key: issue_key.to_owned(),
+                    orphaned,
+                    ..Default::default()
+                };
+                (thread_id, link)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn oldest_thread_is_the_primary() {
+        let mut links = links(&[
+            (3, "BUG-1", false),
+            (1, "BUG-1", false),
+            (2, "BUG-1", false),
+        ]);
+        assign_primary(&mut links, "BUG-1");
+
+        assert_eq!(links[&1].follows, None);
+        assert_eq!(links[&2].follows, Some(1));
+        assert_eq!(links[&3].follows, Some(1));
+    }
+
+    #[test]
+    fn existing_primary_stays_put() {
+        let mut links = links(&[(1, "BUG-1", false), (2, "BUG-1", false)]);
+        links.get_mut(&1).unwrap().follows = Some(2);
+        assign_primary(&mut links, "BUG-1");
+
+        assert_eq!(links[&2].follows, None);
+        assert_eq!(links[&1].follows, Some(2));
+    }
+
+    #[test]
+    fn orphaned_primary_hands_over_to_a_live_thread() {
+        let mut links = links(&[(1, "BUG-1", false), (2, "BUG-1", false)]);
+        assign_primary(&mut links, "BUG-1");
+        links.get_mut(&1).unwrap().orphaned = true;
+        assign_primary(&mut links, "BUG-1");
+
+        assert_eq!(links[&2].follows, None);
+        assert_eq!(links[&1].follows, Some(2));
+    }
+
+    #[test]
+    fn other_issues_are_left_alone() {
+        let mut links = links(&[(1, "BUG-1", false), (2, "BUG-2", false)]);
+        assign_primary(&mut links, "BUG-1");
+
+        assert_eq!(links[&1].follows, None);
+        assert_eq!(links[&2].follows, None);
+    }
+}
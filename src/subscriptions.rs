@@ -0,0 +1,312 @@
+use serde::{Deserialize, Serialize};
+use twilight_model::{
+    application::interaction::Interaction,
+    channel::message::{
+        component::{ActionRow, ButtonStyle},
+        Component, Embed,
+    },
+    id::{marker::UserMarker, Id},
+};
+
+use crate::{
+    context::Context,
+    embeds,
+    error::Result,
+    interactions::{self, respond_ephemeral},
+    metrics::{self, DISCORD_REQUEST_DURATION},
+    models::{JiraChangelogItem, JiraIssueDetails, JiraWebhookComment},
+    utils,
+};
+
+/// prefix of the buttons in subscription DMs, followed by the action and issue key
+pub const SUBSCRIPTION_BUTTON_ID: &str = "subscription";
+
+/// Kinds of issue updates subscribers can choose to get DMs about.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SubscriptionEvent {
+    Status,
+    Resolution,
+    Comment,
+}
+
+impl SubscriptionEvent {
+    /// Events in an issue's changelog that subscribers can be notified about.
+    pub fn from_changelog(items: &[JiraChangelogItem]) -> Vec<SubscriptionEvent> {
+        items
+            .iter()
+            .filter_map(|item| match item.field.as_str() {
+                "status" => Some(SubscriptionEvent::Status),
+                "resolution" => Some(SubscriptionEvent::Resolution),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// A user's issue subscriptions and which updates they want DMs about.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Subscriber {
+    pub issue_keys: Vec<String>,
+    pub status: bool,
+    pub resolution: bool,
+    pub comments: bool,
+    /// turns off every subscription DM without forgetting the subscriptions
+    pub opted_out: bool,
+}
+
+impl Default for Subscriber {
+    fn default() -> Self {
+        Subscriber {
+            issue_keys: Vec::new(),
+            status: true,
+            resolution: true,
+            comments: true,
+            opted_out: false,
+        }
+    }
+}
+
+impl Subscriber {
+    fn wants(&self, event: SubscriptionEvent) -> bool {
+        match event {
+            SubscriptionEvent::Status => self.status,
+            SubscriptionEvent::Resolution => self.resolution,
+            SubscriptionEvent::Comment => self.comments,
+        }
+    }
+
+    /// Whether the user gets any subscription DMs at all.
+    pub fn gets_dms(&self) -> bool {
+        !self.opted_out && (self.status || self.resolution || self.comments)
+    }
+
+    /// The user's preferences and subscriptions, as shown by `/jira notifications`.
+    pub fn summary(&self) -> String {
+        let on_off = |enabled: bool| if enabled { "on" } else { "off" };
+
+        let mut summary = format!(
+            "DMs: {}\nStatus changes: {}\nResolutions: {}\nComments: {}",
+            on_off(!self.opted_out),
+            on_off(self.status),
+            on_off(self.resolution),
+            on_off(self.comments)
+        );
+
+        if self.issue_keys.is_empty() {
+            summary.push_str("\nYou aren't subscribed to any issues.");
+        } else {
+            summary.push_str(&format!("\nSubscribed to {}", self.issue_keys.join(", ")));
+        }
+
+        summary
+    }
+}
+
+/// Subscribes the user to updates about the issue, returns `false` if they already were.
+pub fn subscribe(ctx: &Context, user_id: u64, issue_key: &str) -> Result<bool> {
+    let mut subscribed = false;
+
+    ctx.subscribers.upsert(user_id, |subscriber| {
+        if !subscriber.issue_keys.iter().any(|key| key == issue_key) {
+            subscriber.issue_keys.push(issue_key.to_owned());
+            subscribed = true;
+        }
+    })?;
+
+    Ok(subscribed)
+}
+
+/// Unsubscribes the user from the issue, returns `false` if they weren't subscribed.
+pub fn unsubscribe(ctx: &Context, user_id: u64, issue_key: &str) -> Result<bool> {
+    let subscribed = ctx
+        .subscribers
+        .get(user_id)
+        .is_some_and(|subscriber| subscriber.issue_keys.iter().any(|key| key == issue_key));
+
+    if subscribed {
+        ctx.subscribers.upsert(user_id, |subscriber| {
+            subscriber.issue_keys.retain(|key| key != issue_key)
+        })?;
+    }
+
+    Ok(subscribed)
+}
+
+/// Moves subscriptions to an issue that got a new key, e.g. after moving it to another project.
+pub fn rename_issue(ctx: &Context, old_key: &str, new_key: &str) -> Result<()> {
+    for (user_id, subscriber) in ctx.subscribers.entries() {
+        if subscriber.issue_keys.iter().any(|key| key == old_key) {
+            ctx.subscribers.upsert(user_id, |subscriber| {
+                subscriber
+                    .issue_keys
+                    .retain(|key| key != old_key && key != new_key);
+                subscriber.issue_keys.push(new_key.to_owned());
+            })?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Drops every subscription to an issue that no longer exists.
+pub fn forget_issue(ctx: &Context, issue_key: &str) -> Result<()> {
+    for (user_id, _) in ctx.subscribers.entries() {
+        unsubscribe(ctx, user_id, issue_key)?;
+    }
+
+    Ok(())
+}
+
+/// Moves followers that older versions stored on the links into the subscriber store.
+pub fn migrate_link_subscribers(ctx: &Context) -> Result<()> {
+    for link in ctx.links.all() {
+        if link.subscribers.is_empty() {
+            continue;
+        }
+
+        for user_id in &link.subscribers {
+            subscribe(ctx, *user_id, &link.issue_key)?;
+        }
+        ctx.links
+            .update(link.thread_id, |link| link.subscribers.clear())?;
+
+        tracing::info!(
+            thread_id = link.thread_id,
+            count = link.subscribers.len(),
+            "migrated issue followers to subscriptions"
+        );
+    }
+
+    Ok(())
+}
+
+/// Whether the issue has a bug report thread. Issues without one are internal, so nobody
+/// can subscribe to them or gets DMs about them.
+pub fn is_public(ctx: &Context, issue_key: &str) -> bool {
+    ctx.links
+        .find_by_issue(issue_key)
+        .is_some_and(|link| !link.orphaned)
+}
+
+/// Users subscribed to the issue who want DMs about any of the events.
+fn subscribers_for(ctx: &Context, issue_key: &str, events: &[SubscriptionEvent]) -> Vec<u64> {
+    if !is_public(ctx, issue_key) {
+        return Vec::new();
+    }
+
+    ctx.subscribers
+        .entries()
+        .into_iter()
+        .filter(|(_, subscriber)| {
+            !subscriber.opted_out
+                && subscriber.issue_keys.iter().any(|key| key == issue_key)
+                && events.iter().any(|event| subscriber.wants(*event))
+        })
+        .map(|(user_id, _)| user_id)
+        .collect()
+}
+
+/// Sends a DM about the issue update to everyone subscribed to it.
+pub async fn notify_issue_update(
+    ctx: &Context,
+    issue: &JiraIssueDetails,
+    events: &[SubscriptionEvent],
+    changed_by: Option<&str>,
+) -> Result<()> {
+    let content = format!("{} was updated.", issue.key);
+    let embeds = [embeds::issue_embed(issue, changed_by)];
+
+    for user_id in subscribers_for(ctx, &issue.key, events) {
+        // one user having DMs closed shouldn't stop the others from being notified
+        if let Err(error) = send_dm(ctx, user_id, &issue.key, &content, &embeds).await {
+            tracing::warn!(%error, user_id, "couldn't send subscription DM");
+        }
+    }
+
+    Ok(())
+}
+
+/// Sends a DM with a new comment on the issue to everyone subscribed to it, unless the
+/// comment is restricted to a group or role.
+pub async fn notify_comment(
+    ctx: &Context,
+    issue: &JiraIssueDetails,
+    comment: &JiraWebhookComment,
+) -> Result<()> {
+    if comment.visibility.is_some() {
+        return Ok(());
+    }
+
+    let author = comment
+        .author
+        .as_ref()
+        .map_or("Someone", |author| author.display_name.as_str());
+    let content = utils::truncate_message(format!(
+        "{} commented on {}: {}\n>>> {}",
+        author, issue.key, issue.fields.summary, comment.body
+    ));
+
+    for user_id in subscribers_for(ctx, &issue.key, &[SubscriptionEvent::Comment]) {
+        if let Err(error) = send_dm(ctx, user_id, &issue.key, &content, &[]).await {
+            tracing::warn!(%error, user_id, "couldn't send subscription DM");
+        }
+    }
+
+    Ok(())
+}
+
+async fn send_dm(
+    ctx: &Context,
+    user_id: u64,
+    issue_key: &str,
+    content: &str,
+    embeds: &[Embed],
+) -> Result<()> {
+    let channel = ctx
+        .discord
+        .create_private_channel(Id::<UserMarker>::new(user_id));
+    let channel = metrics::observe(&DISCORD_REQUEST_DURATION, "create_private_channel", channel)
+        .await?
+        .model()
+        .await?;
+
+    let components = [Component::ActionRow(ActionRow {
+        components: vec![interactions::button(
+            format!("{}:unsubscribe:{}", SUBSCRIPTION_BUTTON_ID, issue_key),
+            "Unsubscribe",
+            ButtonStyle::Secondary,
+        )],
+    })];
+    let request = ctx
+        .discord
+        .create_message(channel.id)
+        .content(content)?
+        .embeds(embeds)?
+        .components(&components)?;
+    metrics::observe(&DISCORD_REQUEST_DURATION, "create_message", request).await?;
+
+    Ok(())
+}
+
+/// Handles the unsubscribe button in subscription DMs.
+pub async fn handle_button(ctx: &Context, interaction: &Interaction, target: &str) -> Result<()> {
+    let (action, issue_key) = target.split_once(':').unwrap_or((target, ""));
+    let user_id = match interaction.author_id() {
+        Some(user_id) => user_id.get(),
+        None => return Ok(()),
+    };
+
+    match action {
+        "unsubscribe" => {
+            let content = if unsubscribe(ctx, user_id, issue_key)? {
+                format!("You won't get DMs about {} anymore.", issue_key)
+            } else {
+                format!("You weren't subscribed to {}.", issue_key)
+            };
+
+            respond_ephemeral(ctx, interaction, &content).await
+        }
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,188 @@
+use lazy_static::lazy_static;
+use regex::Regex;
+use twilight_model::{channel::Channel, id::Id};
+
+use crate::{constants, context::Context, error::Result, utils};
+
+lazy_static! {
+    /// everything a bug report has to contain, as `(description, pattern)`
+    static ref REQUIREMENTS: Vec<(String, Regex)> = constants::TEMPLATE_REQUIRED_HEADINGS
+        .iter()
+        .map(|heading| heading_requirement(heading))
+        .chain(
+            constants::TEMPLATE_REQUIRED_PATTERNS
+                .iter()
+                .map(|(name, pattern)| (name.clone(), Regex::new(pattern).unwrap())),
+        )
+        .collect();
+}
+
+/// Requirement for a line with just the heading, ignoring markdown around it and a trailing colon.
+fn heading_requirement(heading: &str) -> (String, Regex) {
+    let pattern = format!(r"(?im)^[\s*_#>]*{}[\s*_:]*$", regex::escape(heading));
+    (heading.to_owned(), Regex::new(&pattern).unwrap())
+}
+
+/// Descriptions of the template requirements the post doesn't meet.
+pub fn missing_requirements(content: &str) -> Vec<&'static str> {
+    unmet_requirements(&REQUIREMENTS, content)
+}
+
+fn unmet_requirements<'a>(requirements: &'a [(String, Regex)], content: &str) -> Vec<&'a str> {
+    requirements
+        .iter()
+        .filter(|(_, pattern)| !pattern.is_match(content))
+        .map(|(name, _)| name.as_str())
+        .collect()
+}
+
+fn format_missing(missing: &[&str]) -> String {
+    missing
+        .iter()
+        .map(|requirement| format!("- {}", requirement))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Checks a newly created bug report against the template, asking the reporter for
+/// whatever is missing and tagging the post as needing more info.
+pub async fn check_new_post(ctx: &Context, thread: &Channel) -> Result<()> {
+    if thread.parent_id != Some(Id::new(*constants::BUG_REPORT_CHANNEL_ID))
+        || thread.newly_created != Some(true)
+        || REQUIREMENTS.is_empty()
+    {
+        return Ok(());
+    }
+
+    let thread_id = thread.id.get();
+
+    // posts the bot made itself, e.g. through Send to Jira, are already being synced
+    let posted_by_bot = thread
+        .owner_id
+        .is_some_and(|owner_id| ctx.bot_user_id.get() == Some(&owner_id.get()));
+    if posted_by_bot || ctx.jobs.get(thread_id).is_some() || ctx.links.get(thread_id).is_some() {
+        return Ok(());
+    }
+
+    let message = utils::get_starter_message(ctx, thread_id).await?;
+    let missing = missing_requirements(&message.content);
+
+    if missing.is_empty() {
+        return Ok(());
+    }
+
+    tracing::info!(thread_id, ?missing, "bug report doesn't match the template");
+
+    let reply = format!(
+        "Thanks for the report! Before we can look into it, please edit your post to include:\n{}",
+        format_missing(&missing)
+    );
+    utils::send_update_to_user_report(ctx, thread_id, &reply).await?;
+
+    if let Some(needs_info_tag_id) = *constants::NEEDS_INFO_TAG_ID {
+        let mut tags = thread.applied_tags.clone().unwrap_or_default();
+        tags.push(Id::new(needs_info_tag_id));
+        utils::update_thread_tags(ctx, thread_id, &tags).await?;
+    }
+
+    Ok(())
+}
+
+/// Whether the bug report may be synced to Jira, i.e. it matches the template or a
+/// moderator overrode the check.
+///
+/// A post that is allowed through loses its "needs info" tag. One that isn't has the
+/// sync tag taken back off and staff are told what's missing.
+pub async fn check_before_sync(ctx: &Context, thread: &Channel) -> Result<bool> {
+    let thread_id = thread.id.get();
+    let tags = thread.applied_tags.clone().unwrap_or_default();
+
+    let missing = if ctx.template_overrides.contains(thread_id) {
+        Vec::new()
+    } else {
+        let message = utils::get_starter_message(ctx, thread_id).await?;
+        missing_requirements(&message.content)
+    };
+
+    if missing.is_empty() {
+        if let Some(needs_info_tag_id) = *constants::NEEDS_INFO_TAG_ID {
+            if tags.iter().any(|tag| tag.get() == needs_info_tag_id) {
+                let tags = tags
+                    .into_iter()
+                    .filter(|tag| tag.get() != needs_info_tag_id)
+                    .collect::<Vec<_>>();
+                utils::update_thread_tags(ctx, thread_id, &tags).await?;
+            }
+        }
+
+        return Ok(true);
+    }
+
+    tracing::info!(
+        thread_id,
+        ?missing,
+        "refusing to sync bug report that doesn't match the template"
+    );
+
+    utils::remove_sync_tag(ctx, thread_id, &tags).await?;
+
+    let notice = format!(
+        "<#{}> wasn't synced to Jira because the post is missing:\n{}\nAsk the reporter to add it, or use `/jira override` in the thread to sync it anyway.",
+        thread_id,
+        format_missing(&missing)
+    );
+    utils::notify_staff(ctx, thread_id, &notice).await?;
+
+    Ok(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn requirements() -> Vec<(String, Regex)> {
+        vec![
+            heading_requirement("Steps to reproduce"),
+            heading_requirement("Platform"),
+            (
+                "an app version".to_owned(),
+                Regex::new(r"\d+\.\d+").unwrap(),
+            ),
+        ]
+    }
+
+    #[test]
+    fn headings_match_with_markdown_and_colons() {
+        let content = "**Steps to reproduce:**\n1. Open the app\n## platform\nAndroid 1.2";
+
+        assert!(unmet_requirements(&requirements(), content).is_empty());
+    }
+
+    #[test]
+    fn headings_have_to_be_on_their_own_line() {
+        let content = "I don't know the steps to reproduce, platform is Android";
+
+        assert_eq!(
+            unmet_requirements(&requirements(), content),
+            vec!["Steps to reproduce", "Platform", "an app version"]
+        );
+    }
+
+    #[test]
+    fn missing_requirements_keep_their_order() {
+        let content = "Platform\niOS";
+
+        assert_eq!(
+            unmet_requirements(&requirements(), content),
+            vec!["Steps to reproduce", "an app version"]
+        );
+    }
+
+    #[test]
+    fn format_missing_lists_requirements() {
+        assert_eq!(
+            format_missing(&["Platform", "an app version"]),
+            "- Platform\n- an app version"
+        );
+    }
+}
@@ -0,0 +1,78 @@
+use serde_json::json;
+use twilight_model::{channel::Channel, id::Id};
+
+use crate::{
+    constants,
+    context::Context,
+    error::Result,
+    metrics::{self, DISCORD_REQUEST_DURATION},
+    utils,
+};
+
+/// Whether title changes made on the side ("discord" or "jira") are synced to the other one.
+fn syncs_from(side: &str) -> bool {
+    let mode = constants::TITLE_SYNC.as_str();
+    mode == "both" || mode == side
+}
+
+/// Copies a linked thread's new name to its issue's summary.
+pub async fn thread_renamed(ctx: &Context, thread: &Channel) -> Result<()> {
+    let thread_id = thread.id.get();
+
+    // threads following another thread's issue keep their own names
+    let (link, name) = match ctx.links.get(thread_id).zip(thread.name.as_ref()) {
+        Some((link, name)) if link.follows.is_none() => (link, name),
+        _ => return Ok(()),
+    };
+
+    // renames made by the bot itself already match, which keeps the two sides from ping-ponging
+    if link.title.as_ref() == Some(name) {
+        return Ok(());
+    }
+
+    ctx.links
+        .update(thread_id, |link| link.title = Some(name.clone()))?;
+
+    // links from before titles were tracked only start tracking here
+    if link.title.is_none() || !syncs_from("discord") {
+        return Ok(());
+    }
+
+    utils::update_jira_issue(
+        ctx,
+        &link.issue_key,
+        &json!({ "fields": { "summary": name } }),
+    )
+    .await?;
+
+    tracing::info!(thread_id, jira_key = %link.issue_key, "copied thread name to summary");
+
+    Ok(())
+}
+
+/// Renames the issue's primary thread after its summary was changed in Jira.
+pub async fn summary_changed(ctx: &Context, thread_id: u64, summary: &str) -> Result<()> {
+    let link = match ctx.links.get(thread_id) {
+        Some(link) if link.follows.is_none() && syncs_from("jira") => link,
+        _ => return Ok(()),
+    };
+
+    // thread names are capped at 100 characters
+    let name = summary.chars().take(100).collect::<String>();
+
+    // summaries the bot copied from the thread come back through the webhook too
+    if link.title.as_deref() == Some(name.as_str()) {
+        return Ok(());
+    }
+
+    // remembered before renaming, so the thread update isn't copied back to Jira
+    ctx.links
+        .update(thread_id, |link| link.title = Some(name.clone()))?;
+
+    let request = ctx.discord.update_thread(Id::new(thread_id)).name(&name)?;
+    metrics::observe(&DISCORD_REQUEST_DURATION, "update_thread", request).await?;
+
+    tracing::info!(thread_id, jira_key = %link.issue_key, "copied summary to thread name");
+
+    Ok(())
+}
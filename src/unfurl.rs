@@ -0,0 +1,145 @@
+use lazy_static::lazy_static;
+use regex::Regex;
+use twilight_model::{
+    channel::{message::AllowedMentions, Message},
+    id::Id,
+};
+
+use crate::{
+    constants,
+    context::Context,
+    embeds,
+    error::Result,
+    gateway::unix_now,
+    metrics::{self, DISCORD_REQUEST_DURATION},
+    utils,
+};
+
+/// at most this many issues are unfurled for a single message
+const MAX_UNFURLS: usize = 3;
+
+lazy_static! {
+    /// issue keys of the unfurled projects, e.g. "CELLS-123"
+    static ref ISSUE_KEY: Regex = Regex::new(&format!(
+        r"\b(?:{})-\d+\b",
+        constants::UNFURL_PROJECTS
+            .iter()
+            .map(|project| regex::escape(project))
+            .collect::<Vec<_>>()
+            .join("|")
+    ))
+    .unwrap();
+}
+
+/// Whether issue keys are unfurled in the channel. Threads follow their parent channel
+/// unless they were turned on or off themselves.
+pub fn is_enabled(ctx: &Context, channel_id: u64) -> bool {
+    let parent_id = ctx
+        .cache
+        .channel(Id::new(channel_id))
+        .and_then(|channel| channel.parent_id)
+        .map(|parent_id| parent_id.get());
+
+    let channel_ids = [Some(channel_id), parent_id];
+
+    // channels turned on or off with `/jira unfurl` win over the configured ones
+    if let Some(enabled) = channel_ids
+        .iter()
+        .flatten()
+        .find_map(|id| ctx.unfurl_channels.get(*id))
+    {
+        return enabled;
+    }
+
+    channel_ids
+        .iter()
+        .flatten()
+        .any(|id| constants::UNFURL_CHANNEL_IDS.contains(id))
+}
+
+/// Starts the cooldown for the issue in the channel, returns `false` if it's still cooling down.
+fn start_cooldown(ctx: &Context, channel_id: u64, key: &str) -> bool {
+    let now = unix_now();
+    let mut cooldowns = ctx.unfurl_cooldowns.lock().unwrap();
+
+    // forget expired cooldowns so the map doesn't grow forever
+    cooldowns.retain(|_, unfurled_at| {
+        now.saturating_sub(*unfurled_at) < *constants::UNFURL_COOLDOWN_SECS
+    });
+
+    let cooldown = (channel_id, key.to_owned());
+    if cooldowns.contains_key(&cooldown) {
+        return false;
+    }
+
+    cooldowns.insert(cooldown, now);
+    true
+}
+
+/// Replies to a message mentioning issue keys with a compact summary of each issue.
+pub async fn unfurl_issue_keys(ctx: &Context, message: &Message) -> Result<()> {
+    let channel_id = message.channel_id.get();
+
+    if message.author.bot || !is_enabled(ctx, channel_id) {
+        return Ok(());
+    }
+
+    let mut keys = Vec::new();
+    for key in ISSUE_KEY.find_iter(&message.content) {
+        if !keys.contains(&key.as_str()) {
+            keys.push(key.as_str());
+        }
+    }
+
+    let mut issues = Vec::new();
+    for key in keys.into_iter().take(MAX_UNFURLS) {
+        if !start_cooldown(ctx, channel_id, key) {
+            continue;
+        }
+
+        // keys of issues that don't exist are just ignored, they're often typos
+        match utils::get_jira_issue(ctx, key).await {
+            Ok(issue) => issues.push(issue),
+            Err(error) if error.is_not_found() => {}
+            Err(error) => return Err(error),
+        }
+    }
+
+    if issues.is_empty() {
+        return Ok(());
+    }
+
+    let thread_id = |key: &str| {
+        ctx.links
+            .find_by_issue(key)
+            .filter(|link| !link.orphaned)
+            .map(|link| link.thread_id)
+    };
+    let embeds = issues
+        .iter()
+        .map(|issue| embeds::compact_issue_embed(issue, thread_id(&issue.key)))
+        .collect::<Vec<_>>();
+    let text = issues
+        .iter()
+        .map(|issue| embeds::compact_issue_text(issue, thread_id(&issue.key)))
+        .collect::<Vec<_>>()
+        .join("\n\n");
+
+    // don't ping whoever mentioned the issues
+    let allowed_mentions = AllowedMentions::default();
+    let request = ctx
+        .discord
+        .create_message(message.channel_id)
+        .reply(message.id)
+        .allowed_mentions(Some(&allowed_mentions));
+    let request = if *constants::PLAIN_TEXT_UPDATES {
+        request.content(&text)?
+    } else {
+        request.embeds(&embeds)?
+    };
+    metrics::observe(&DISCORD_REQUEST_DURATION, "create_message", request).await?;
+
+    tracing::info!(channel_id, count = issues.len(), "unfurled issue keys");
+
+    Ok(())
+}
@@ -1,50 +1,146 @@
-use std::env;
-use twilight_cache_inmemory::InMemoryCache;
+use std::sync::Arc;
+
+use reqwest::Method;
+use tracing::Instrument;
 use twilight_gateway::Event;
-use twilight_http::Client as HttpClient;
 use twilight_model::{
-    channel::{Channel, Attachment, Message},
+    channel::{Attachment, Channel, Message},
     id::{
-        marker::{ChannelMarker, GuildMarker, MessageMarker},
+        marker::{ChannelMarker, GuildMarker, MessageMarker, TagMarker},
         Id,
     },
 };
 
 use crate::{
+    affected, audit,
     constants::{self, GUILD_ID},
+    context::Context,
+    edits,
+    error::{Error, Result},
+    gateway::unix_now_millis,
+    jobs::SyncJob,
+    metrics::{self, ATTACHMENTS_UPLOADED, DISCORD_REQUEST_DURATION, ISSUES_CREATED},
     models::{
-        IssueFields, IssueType, JiraIssue, ParsedMessageURL, Project, CreateJiraIssueResponse,
+        CreateJiraIssueResponse, IssueFields, IssueType, JiraComment, JiraIssue, JiraIssueDetails,
+        JiraTransitions, ParsedMessageURL, Project,
     },
+    report::BugReport,
+    review, status,
+    storage::IssueLink,
+    template,
 };
 
+/// label in front of the Discord url in issue descriptions
+pub const REPORT_ORIGIN: &str = "Report Origin: ";
+
 pub async fn send_update_to_user_report(
+    ctx: &Context,
     channel_id: u64,
     message: &str,
-) -> Result<(), Box<dyn std::error::Error>> {
-    // Create a new HTTP client using your Discord bot token
-    let http = HttpClient::new(env::var("DISCORD_TOKEN")?);
-
+) -> Result<()> {
     // Send the message to the bug report channel
-    let request = http
+    let request = ctx
+        .discord
         .create_message(Id::<ChannelMarker>::new(channel_id))
-        .content(message)?
-        .await?;
+        .content(message)?;
+    let request = metrics::observe(&DISCORD_REQUEST_DURATION, "create_message", request).await?;
 
     if request.status().is_success() {
-        println!("Message sent successfully");
+        tracing::info!(channel_id, "Message sent successfully");
     } else {
-        println!("Failed to send message");
+        tracing::warn!(channel_id, status = %request.status(), "Failed to send message");
     }
 
     Ok(())
 }
 
+/// Whether the message starts the thread it was sent in.
+pub fn is_starter_message(message_id: u64, channel_id: u64) -> bool {
+    // the starter message of a forum post has the same id as the post itself
+    message_id == channel_id
+}
+
+/// Cuts the content down to the 2000 characters a message can hold, marking where it was cut.
+pub fn truncate_message(content: String) -> String {
+    if content.chars().count() <= 2000 {
+        return content;
+    }
+
+    content.chars().take(1997).collect::<String>() + "..."
+}
+
+/// Parses the thread id out of a component's custom id, `what` names the component in errors.
+pub fn parse_thread_id(target: &str, what: &str) -> Result<u64> {
+    target
+        .parse::<u64>()
+        .ok()
+        .filter(|thread_id| *thread_id != 0)
+        .ok_or_else(|| Error::Parse(format!("invalid {} target: {}", what, target)))
+}
+
+/// Threads the issue is synced to, the primary one first, from the stored links or else the
+/// report origin in its description.
+///
+/// Threads only found through the report origin get a link, so their status card can be kept.
+pub async fn threads_for_issue(ctx: &Context, issue: &JiraIssueDetails) -> Result<Vec<u64>> {
+    let links = ctx.links.find_all_by_issue(&issue.key);
+
+    // orphaned threads are gone, the report origin would only point at them again
+    if !links.is_empty() {
+        return Ok(links
+            .into_iter()
+            .filter(|link| !link.orphaned)
+            .map(|link| link.thread_id)
+            .collect());
+    }
+
+    let thread_id = match issue
+        .fields
+        .description
+        .as_deref()
+        .and_then(|description| parse_message_url_from_issue_update(description).ok())
+    {
+        Some(url) => url.channel_id.get(),
+        None => return Ok(Vec::new()),
+    };
+
+    // the thread has since been linked to a different issue
+    if ctx.links.get(thread_id).is_some() {
+        return Ok(Vec::new());
+    }
+
+    let message = match get_starter_message(ctx, thread_id).await {
+        Ok(message) => message,
+        Err(error) if error.is_not_found() => return Ok(Vec::new()),
+        Err(error) => return Err(error),
+    };
+
+    ctx.links.insert(IssueLink {
+        thread_id,
+        issue_key: issue.key.clone(),
+        reporter_id: status::reporter_id(ctx, &message),
+        description: Some(report_description(&message.content)),
+        ..Default::default()
+    })?;
+
+    tracing::info!(thread_id, jira_key = %issue.key, "linked thread from the report origin");
+
+    Ok(vec![thread_id])
+}
+
 // NOTE: have to use this due to the markdown shit for jira won't work
 /// The format will look like the following:
 /// "Report Origin: https://discord.com/channels/123456789/987654321/987654321"
-pub fn parse_message_url_from_issue_update(description: &str) -> ParsedMessageURL {
-    let start_index = description.find("Report Origin: ").unwrap();
-    let end_index = description.find(')').unwrap() - 1;
+pub fn parse_message_url_from_issue_update(description: &str) -> Result<ParsedMessageURL> {
+    let start_index = description
+        .find(REPORT_ORIGIN)
+        .ok_or_else(|| Error::Parse("description has no report origin".to_owned()))?;
+
+    // the url ends at the first whitespace after the label
+    let end_index = description[start_index + REPORT_ORIGIN.len()..]
+        .find(char::is_whitespace)
+        .map(|index| start_index + REPORT_ORIGIN.len() + index)
+        .unwrap_or(description.len());
 
     // get the substring
     let message_url = &description[start_index..end_index];
@@ -54,36 +150,62 @@ pub fn parse_message_url_from_issue_update(description: &str) -> ParsedMessageUR
     // ["Report Origin: https:" ,"", "discord.com", "channels", "123456789", "987654321", "987654321"]
     let message_url_parts: Vec<&str> = message_url.split('/').collect();
 
-    // get the server ID
-    let server_id = message_url_parts[4];
+    let parse_id = |index: usize| {
+        message_url_parts
+            .get(index)
+            .and_then(|part| part.parse::<u64>().ok())
+            .filter(|id| *id != 0)
+            .ok_or_else(|| Error::Parse(format!("invalid report origin url: {}", message_url)))
+    };
 
-    // get the channel ID
-    let channel_id = message_url_parts[5];
+    // create the message URL
+    Ok(ParsedMessageURL {
+        server_id: Id::<GuildMarker>::new(parse_id(4)?),
+        channel_id: Id::<ChannelMarker>::new(parse_id(5)?),
+        message_id: Id::<MessageMarker>::new(parse_id(6)?),
+    })
+}
 
-    // get the message ID
-    let message_id = message_url_parts[6];
+pub fn thread_url(thread_id: u64) -> String {
+    format!("https://discord.com/channels/{}/{}", *GUILD_ID, thread_id)
+}
 
-    // create the message URL
-    ParsedMessageURL {
-        server_id: Id::<GuildMarker>::new(server_id.parse::<u64>().unwrap()),
-        channel_id: Id::<ChannelMarker>::new(channel_id.parse::<u64>().unwrap()),
-        message_id: Id::<MessageMarker>::new(message_id.parse::<u64>().unwrap()),
+pub fn issue_url(issue_key: &str) -> String {
+    format!("{}/browse/{}", *constants::JIRA_BASE_URL, issue_key)
+}
+
+/// Whether the string looks like a Jira issue key, e.g. "CELLS-123".
+pub fn is_issue_key(key: &str) -> bool {
+    match key.split_once('-') {
+        Some((project, number)) => {
+            project.starts_with(|c: char| c.is_ascii_uppercase())
+                && project
+                    .chars()
+                    .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+                && !number.is_empty()
+                && number.chars().all(|c| c.is_ascii_digit())
+        }
+        None => false,
     }
 }
 
-pub async fn handle_tag_updates(
-    cache: &InMemoryCache,
-    event: &Event,
-) -> Result<(), Box<dyn std::error::Error>> {
+/// Id of the forum tag that marks a bug report as synced to Jira.
+pub fn sync_tag_id() -> Result<u64> {
+    dotenv::var("JIRA_SYNC_TAG_ID")
+        .map_err(|_| Error::Config("JIRA_SYNC_TAG_ID is not set".to_owned()))?
+        .parse::<u64>()
+        .map_err(|_| Error::Config("JIRA_SYNC_TAG_ID is not a valid id".to_owned()))
+}
+
+pub async fn handle_tag_updates(ctx: &Arc<Context>, event: &Event) -> Result<()> {
     // Handle the tag update event
     if let Event::ThreadUpdate(new_channel) = event {
         // ensure the channel is a thread within the bug report channel
-        if new_channel.parent_id.unwrap() != *constants::BUG_REPORT_CHANNEL_ID {
+        if new_channel.parent_id != Some(Id::new(*constants::BUG_REPORT_CHANNEL_ID)) {
             return Ok(());
         }
 
-        let old_channel = cache
-            .channel(new_channel.id);
+        let old_channel = ctx.cache.channel(new_channel.id);
 
         if old_channel.is_none() {
             return Ok(());
@@ -99,10 +221,7 @@ pub async fn handle_tag_updates(
         let old_channel_tags = old_channel.and_then(|c| c.applied_tags.clone());
 
         // get the tag id from the env var
-        let tag_id = dotenv::var("JIRA_SYNC_TAG_ID")
-            .unwrap()
-            .parse::<u64>()
-            .unwrap();
+        let tag_id = sync_tag_id()?;
 
         // check if the new channel has the tag
         let new_channel_has_tag = new_channel_tags.is_some()
@@ -118,37 +237,231 @@ pub async fn handle_tag_updates(
                 .iter()
                 .any(|tag| tag.get() == tag_id);
 
-        // check if the new channel has the tag and the old channel does not
-        if new_channel_has_tag && !old_channel_has_tag {
-            // fetch the first message in the thread/post via fetching for a message within the channel using the id of the channel
-            // since the starter message and post id are the same
-            let http = HttpClient::new(constants::DISCORD_TOKEN.to_string());
+        // check if the new channel has the tag and the old channel does not,
+        // skipping threads that are already linked to an issue
+        if new_channel_has_tag
+            && !old_channel_has_tag
+            && ctx.links.get(new_channel.id.get()).is_none()
+        {
+            // checking who applied the tag can take a few seconds, so it runs on its own task
+            let ctx = Arc::clone(ctx);
+            let thread = new_channel.0.clone();
+            let received_at = unix_now_millis();
+
+            tokio::spawn(
+                async move {
+                    if let Err(error) =
+                        handle_sync_tag_added(&ctx, &thread, tag_id, received_at).await
+                    {
+                        tracing::error!(%error, "error handling sync tag");
+                        metrics::record_error(error.kind());
+                    }
+                }
+                .instrument(tracing::Span::current()),
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Queues a sync for a thread that just got the sync tag, as long as the tag was applied
+/// by someone allowed to and the post matches the template.
+async fn handle_sync_tag_added(
+    ctx: &Arc<Context>,
+    thread: &Channel,
+    tag_id: u64,
+    received_at: u64,
+) -> Result<()> {
+    if !audit::check_sync_tag_authorized(ctx, thread, tag_id, received_at).await?
+        || !template::check_before_sync(ctx, thread).await?
+    {
+        return Ok(());
+    }
+
+    if *constants::REVIEW_MODE {
+        review::request_approval(ctx, thread.id.get()).await
+    } else {
+        ctx.jobs.spawn(ctx, SyncJob::new(thread.id.get()));
+        Ok(())
+    }
+}
+
+pub async fn get_starter_message(ctx: &Context, thread_id: u64) -> Result<Message> {
+    // fetch the first message in the thread/post via fetching for a message within the channel using the id of the channel
+    // since the starter message and post id are the same
+    let message = ctx.discord.message(
+        Id::<ChannelMarker>::new(thread_id),
+        Id::<MessageMarker>::new(thread_id),
+    );
+
+    Ok(
+        metrics::observe(&DISCORD_REQUEST_DURATION, "get_message", message)
+            .await?
+            .model()
+            .await?,
+    )
+}
+
+/// Replaces the forum tags applied to a thread.
+/// Takes the sync tag off a thread with the given tags, if it has it.
+pub async fn remove_sync_tag(ctx: &Context, thread_id: u64, tags: &[Id<TagMarker>]) -> Result<()> {
+    let sync_tag_id = sync_tag_id()?;
+    if !tags.iter().any(|tag| tag.get() == sync_tag_id) {
+        return Ok(());
+    }
+
+    let tags = tags
+        .iter()
+        .copied()
+        .filter(|tag| tag.get() != sync_tag_id)
+        .collect::<Vec<_>>();
+    update_thread_tags(ctx, thread_id, &tags).await
+}
 
-            let message = http
-                .message(new_channel.id, Id::<MessageMarker>::new(new_channel.id.get()))
+pub async fn update_thread_tags(
+    ctx: &Context,
+    thread_id: u64,
+    tags: &[Id<TagMarker>],
+) -> Result<()> {
+    let request = ctx
+        .discord
+        .update_thread(Id::new(thread_id))
+        .applied_tags(Some(tags));
+    metrics::observe(&DISCORD_REQUEST_DURATION, "update_thread", request).await?;
+
+    Ok(())
+}
+
+/// Posts a notice about a thread in the staff channel, or in the thread itself if there is no staff channel.
+pub async fn notify_staff(ctx: &Context, thread_id: u64, message: &str) -> Result<()> {
+    let channel_id = constants::STAFF_CHANNEL_ID.unwrap_or(thread_id);
+
+    send_update_to_user_report(ctx, channel_id, message).await
+}
+
+/// Syncs a bug report thread to Jira, picking up from wherever the job left off.
+pub async fn sync_thread_to_jira(ctx: &Context, thread_id: u64) -> Result<()> {
+    let job = match ctx.jobs.get(thread_id) {
+        Some(job) => job,
+        None => return Ok(()),
+    };
+
+    let channel_id = Id::<ChannelMarker>::new(thread_id);
+    let message = get_starter_message(ctx, thread_id).await?;
+
+    let (issue_key, title) = match job.issue_key.clone() {
+        Some(issue_key) => {
+            tracing::Span::current().record("jira_key", issue_key.as_str());
+
+            let title = ctx
+                .cache
+                .channel(channel_id)
+                .and_then(|channel| channel.name.clone());
+            (issue_key, title)
+        }
+        None => {
+            let channel = ctx.discord.channel(channel_id);
+            let channel = metrics::observe(&DISCORD_REQUEST_DURATION, "get_channel", channel)
                 .await?
                 .model()
                 .await?;
-            
-            let jira_issue_creation = create_jira_issue(&message, new_channel).await.map_err(|error| {
-                println!("Error creating Jira issue: {:?}", error);
-                error
-            })?;
 
-            attach_images_to_jira_issue(&message, &jira_issue_creation.key).await?;
+            let jira_issue_creation = create_jira_issue(ctx, &message, &channel, &job)
+                .await
+                .map_err(|error| {
+                    tracing::error!(?error, "Error creating Jira issue");
+                    error
+                })?;
 
-            // send a message to the user report channel stating that the report is now synced to jira
-            let automated_reply = 
-                format!("This has been added to our bug tracking system as the issue {}.  As we resolve that issue, updates will be posted back here.", jira_issue_creation.key);
+            ctx.jobs.update(thread_id, |job| {
+                job.issue_key = Some(jira_issue_creation.key.clone())
+            });
+            tracing::Span::current().record("jira_key", jira_issue_creation.key.as_str());
+            tracing::info!("created Jira issue");
 
-            send_update_to_user_report(new_channel.id.get(), automated_reply.as_str()).await?;
+            (jira_issue_creation.key, channel.name)
         }
+    };
+
+    // a retried job may have created the issue without getting as far as linking it
+    if ctx.links.get(thread_id).is_none() {
+        ctx.links.insert(IssueLink {
+            thread_id,
+            issue_key: issue_key.clone(),
+            reporter_id: status::reporter_id(ctx, &message),
+            title,
+            description: Some(
+                job.description
+                    .clone()
+                    .unwrap_or_else(|| report_description(&message.content)),
+            ),
+            description_reviewed: job
+                .description
+                .as_ref()
+                .is_some_and(|description| *description != report_description(&message.content)),
+            ..Default::default()
+        })?;
     }
 
+    for attachment in &message.attachments {
+        if job.uploaded_attachments.contains(&attachment.id.get()) {
+            continue;
+        }
+
+        attach_image_to_jira_issue(ctx, attachment, &issue_key).await?;
+
+        ctx.jobs.update(thread_id, |job| {
+            job.uploaded_attachments.push(attachment.id.get())
+        });
+    }
+
+    tracing::info!("successfully attached all images");
+
+    if !job.commented {
+        let mut comment = format!(
+            "Synced from Discord thread {} (correlation id: {})",
+            thread_id, job.correlation_id
+        );
+
+        if let Some(approved_by) = &job.approved_by {
+            comment.push_str(&format!("\nApproved by {} on Discord", approved_by));
+        }
+
+        add_jira_comment(ctx, &issue_key, &comment).await?;
+
+        ctx.jobs.update(thread_id, |job| job.commented = true);
+    }
+
+    if !job.replied {
+        // send a message to the user report channel stating that the report is now synced to jira
+        let automated_reply =
+            format!("This has been added to our bug tracking system as the issue {}.  As we resolve that issue, updates will be posted back here.", issue_key);
+
+        affected::send_sync_reply(ctx, thread_id, automated_reply.as_str()).await?;
+
+        ctx.jobs.update(thread_id, |job| job.replied = true);
+    }
+
+    status::post_status_card(ctx, thread_id).await?;
+
     Ok(())
 }
 
-pub async fn create_jira_issue(message: &Message, channel: &Channel) -> Result<CreateJiraIssueResponse, Box<dyn std::error::Error>> {
+/// Body of the Jira description for a bug report, i.e. everything after the report origin.
+pub fn report_description(content: &str) -> String {
+    // posts following the bug report layout get their sections formatted, anything else is passed through as is
+    BugReport::parse(content)
+        .map(|report| report.to_jira_description())
+        .unwrap_or_else(|| content.to_owned())
+}
+
+pub async fn create_jira_issue(
+    ctx: &Context,
+    message: &Message,
+    channel: &Channel,
+    job: &SyncJob,
+) -> Result<CreateJiraIssueResponse> {
     // use reqwest to create a new Jira issue
     let channel_name = channel
         .name
@@ -190,31 +503,49 @@ pub async fn create_jira_issue(message: &Message, channel: &Channel) -> Result<C
     //     ],
     // };
 
+    // posts following the bug report layout get their sections copied into the configured custom fields
+    let report = BugReport::parse(&message.content);
+
+    let custom_fields = report
+        .iter()
+        .flat_map(|report| {
+            constants::JIRA_REPORT_FIELDS
+                .iter()
+                .filter_map(|(section, field)| {
+                    let text = report.section(section)?;
+                    Some((field.clone(), serde_json::Value::from(text)))
+                })
+        })
+        .collect();
+
     let data = JiraIssue {
         fields: IssueFields {
             project: Project::default(),
-            summary: channel_name,
+            summary: job.summary.clone().unwrap_or(channel_name),
             description: format!(
-                "Report Origin: https://discord.com/channels/{}/{}/{}\n\n{}",
-                GUILD_ID.to_string(),
+                "{}https://discord.com/channels/{}/{}/{}\n\n{}\n\n{}\n",
+                REPORT_ORIGIN,
+                *GUILD_ID,
                 message.channel_id.get(),
                 message.id.get(),
-                message.content
-                // description
+                job.description
+                    .clone()
+                    .unwrap_or_else(|| report_description(&message.content)), // description
+                edits::DEVELOPER_NOTES_HEADING
             ),
-            issuetype: IssueType::default(),
+            issuetype: job
+                .issue_type
+                .as_deref()
+                .map(IssueType::named)
+                .unwrap_or_default(),
             status_category: None,
+            custom_fields,
         },
     };
 
-    let client = reqwest::Client::new();
-
-    let response = client
-        .post("https://computerlunch.atlassian.net/rest/api/2/issue")
-        .basic_auth(
-            dotenv::var("JIRA_USERNAME")?,
-            Some(dotenv::var("JIRA_TOKEN")?),
-        ).json(&data)
+    let request = ctx
+        .jira
+        .request(Method::POST, "issue")
         // .json(&JiraIssue {
         //     fields: IssueFields {
         //         project: Project::default(),
@@ -231,69 +562,201 @@ pub async fn create_jira_issue(message: &Message, channel: &Channel) -> Result<C
         //         status_category: None,
         //     },
         // })
-        .send()
-        .await?.error_for_status()?.json::<CreateJiraIssueResponse>().await?;
+        .json(&data);
+
+    let response = ctx
+        .jira
+        .send("create_issue", request)
+        .await?
+        .json::<CreateJiraIssueResponse>()
+        .await
+        .map_err(|error| Error::Parse(error.to_string()))?;
+
+    ISSUES_CREATED.inc();
 
     Ok(response)
 }
 
 pub async fn attach_images_to_jira_issue(
+    ctx: &Context,
     message: &Message,
     jira_issue_key: &str,
-) -> Result<(), Box<dyn std::error::Error>> {
+) -> Result<()> {
     // get the attachments from the user's message and then attach it to the jira issue through the attachments route in the jira api
     for attachment in &message.attachments {
-        let client = reqwest::Client::new();
-        let attachment = resolve_attachment_data_to_part(attachment).await?;
-
-        client
-            .post(format!(
-                "https://computerlunch.atlassian.net/rest/api/2/issue/{}/attachments",
-                jira_issue_key
-            ))
-            .basic_auth(
-                dotenv::var("JIRA_USERNAME")?,
-                Some(dotenv::var("JIRA_TOKEN")?),
-            ).multipart(
-                reqwest::multipart::Form::new().part(
-                    "file",
-                    attachment,
-                ),
-            ).header("X-Atlassian-Token", "no-check").send()
-            .await?.error_for_status()?;
+        attach_image_to_jira_issue(ctx, attachment, jira_issue_key).await?;
     }
 
-    println!("successfully attached all images for {}", jira_issue_key);
+    tracing::info!(
+        jira_key = jira_issue_key,
+        "successfully attached all images"
+    );
 
     Ok(())
 }
 
-pub async fn download_discord_attachment(
+pub async fn attach_image_to_jira_issue(
+    ctx: &Context,
     attachment: &Attachment,
-) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
-    let client = reqwest::Client::new();
+    jira_issue_key: &str,
+) -> Result<()> {
+    let attachment = resolve_attachment_data_to_part(ctx, attachment).await?;
+
+    let request = ctx
+        .jira
+        .request(
+            Method::POST,
+            &format!("issue/{}/attachments", jira_issue_key),
+        )
+        .multipart(reqwest::multipart::Form::new().part("file", attachment))
+        .header("X-Atlassian-Token", "no-check");
+
+    ctx.jira.send("add_attachment", request).await?;
+
+    ATTACHMENTS_UPLOADED.inc();
+
+    Ok(())
+}
 
-    let response = client
+pub async fn get_jira_issue(ctx: &Context, jira_issue_key: &str) -> Result<JiraIssueDetails> {
+    let request = ctx
+        .jira
+        .request(Method::GET, &format!("issue/{}", jira_issue_key))
+        .query(&[(
+            "fields",
+            "summary,description,status,assignee,priority,resolution,fixVersions,issuelinks,updated",
+        )]);
+
+    ctx.jira
+        .send("get_issue", request)
+        .await?
+        .json::<JiraIssueDetails>()
+        .await
+        .map_err(|error| Error::Parse(error.to_string()))
+}
+
+/// Moves the issue through the transition with the given name, returns `false` if the
+/// transition isn't available from the issue's current status.
+pub async fn transition_jira_issue(
+    ctx: &Context,
+    jira_issue_key: &str,
+    transition_name: &str,
+) -> Result<bool> {
+    let path = format!("issue/{}/transitions", jira_issue_key);
+
+    let transitions = ctx
+        .jira
+        .send("get_transitions", ctx.jira.request(Method::GET, &path))
+        .await?
+        .json::<JiraTransitions>()
+        .await
+        .map_err(|error| Error::Parse(error.to_string()))?;
+
+    let transition = match transitions
+        .transitions
+        .into_iter()
+        .find(|transition| transition.name.eq_ignore_ascii_case(transition_name))
+    {
+        Some(transition) => transition,
+        None => {
+            tracing::warn!(
+                jira_key = jira_issue_key,
+                transition = transition_name,
+                "transition isn't available"
+            );
+            return Ok(false);
+        }
+    };
+
+    let request = ctx
+        .jira
+        .request(Method::POST, &path)
+        .json(&serde_json::json!({ "transition": { "id": transition.id } }));
+    ctx.jira.send("transition_issue", request).await?;
+
+    Ok(true)
+}
+
+/// Edits the issue with a body in the shape Jira expects, i.e. with `fields` and/or `update`.
+pub async fn update_jira_issue(
+    ctx: &Context,
+    jira_issue_key: &str,
+    body: &serde_json::Value,
+) -> Result<()> {
+    let request = ctx
+        .jira
+        .request(Method::PUT, &format!("issue/{}", jira_issue_key))
+        .json(body);
+
+    ctx.jira.send("update_issue", request).await?;
+
+    Ok(())
+}
+
+pub async fn add_jira_comment(ctx: &Context, jira_issue_key: &str, body: &str) -> Result<()> {
+    let request = ctx
+        .jira
+        .request(Method::POST, &format!("issue/{}/comment", jira_issue_key))
+        .json(&JiraComment {
+            body: body.to_owned(),
+        });
+
+    ctx.jira.send("add_comment", request).await?;
+
+    Ok(())
+}
+
+pub async fn download_discord_attachment(
+    ctx: &Context,
+    attachment: &Attachment,
+) -> Result<Vec<u8>> {
+    let response = ctx
+        .http
         .get(attachment.url.clone())
         .send()
-        .await?.error_for_status()?;
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(Error::Download)?;
 
-    Ok(response.bytes().await?.to_vec())
+    Ok(response.bytes().await.map_err(Error::Download)?.to_vec())
 }
 
 pub async fn resolve_attachment_data_to_part(
+    ctx: &Context,
     attachment: &Attachment,
-) -> Result<reqwest::multipart::Part, Box<dyn std::error::Error>> {
-    let client = reqwest::Client::new();
-
-    let response = client
-        .get(attachment.url.clone())
-        .send()
-        .await?.error_for_status()?;
+) -> Result<reqwest::multipart::Part> {
+    let bytes = download_discord_attachment(ctx, attachment).await?;
 
-    let part = reqwest::multipart::Part::bytes(response.bytes().await?.to_vec())
+    let part = reqwest::multipart::Part::bytes(bytes)
         .file_name(attachment.filename.clone())
-        .mime_str(attachment.content_type.clone().unwrap_or_else(|| "image/png".to_owned()).as_str())?;
+        .mime_str(
+            attachment
+                .content_type
+                .clone()
+                .unwrap_or_else(|| "image/png".to_owned())
+                .as_str(),
+        )
+        .map_err(|error| Error::Parse(error.to_string()))?;
 
     Ok(part)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn short_messages_arent_truncated() {
+        let content = "a".repeat(2000);
+
+        assert_eq!(truncate_message(content.clone()), content);
+    }
+
+    #[test]
+    fn long_messages_are_cut_to_the_limit() {
+        let truncated = truncate_message("é".repeat(2001));
+
+        assert_eq!(truncated.chars().count(), 2000);
+        assert!(truncated.ends_with("é..."));
+    }
+}